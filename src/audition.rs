@@ -0,0 +1,45 @@
+use plugin_util::simd::*;
+
+use crate::{one_pole::OnePole, rng::Rng, TAU};
+
+const LEN_MS: f32 = 40.;
+const CUTOFF: f32 = 8000.;
+const LEVEL: f32 = 0.5;
+
+/// A short noise burst, Hann-windowed and lowpassed so that it starts, ends and
+/// tops out smoothly, fed to the filter's input on demand.
+#[derive(Default)]
+pub struct Audition {
+    remaining: usize,
+    len: usize,
+    rng: Rng,
+    lowpass: OnePole,
+}
+
+impl Audition {
+    pub fn reset(&mut self) {
+        self.remaining = 0;
+    }
+
+    /// Starts a burst, always the same one, from the beginning.
+    pub fn trigger(&mut self, two_pi_tick: f32) {
+        self.len = (LEN_MS / 1000. * TAU / two_pi_tick) as usize;
+        self.remaining = self.len;
+        self.rng = Rng::default();
+        self.lowpass.reset();
+        self.lowpass.set_cutoff(two_pi_tick * CUTOFF);
+    }
+
+    pub fn next(&mut self) -> Option<f32x2> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let t = 1. - self.remaining as f32 / self.len as f32;
+        let window = 0.5 - 0.5 * f32::cos(TAU * t);
+        self.remaining -= 1;
+
+        let noise = Simd::splat(self.rng.next_f32() * LEVEL * window);
+        Some(self.lowpass.process(noise))
+    }
+}
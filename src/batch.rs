@@ -0,0 +1,222 @@
+use core::simd::{f32x4, simd_swizzle};
+use plugin_util::simd::*;
+
+use crate::{biquad::Prototype, precise};
+
+/// `g`, `k`, and how much of the input, band and low outputs make up the output
+type Coefficients = [f32x2; 5];
+
+/// `[lo, hi]`, as one vector
+fn join(lo: f32x2, hi: f32x2) -> f32x4 {
+    simd_swizzle!(lo, hi, [0, 1, 2, 3])
+}
+
+/// `[x, x]`
+fn twice(x: f32x2) -> f32x4 {
+    join(x, x)
+}
+
+/// Two frames of the SVF at once, in its state-space form `s' = A·s + B·x, y = C·s + D·x`,
+/// `s` being both integrators' states. Vectors hold the first and second frames', or the
+/// first and second states', values, for both channels.
+struct Kernel {
+    /// The columns of `A²`, then `A·B` and `B`, the next state coming out as
+    /// `a2_1·s1 + a2_2·s2 + ab·x0 + b·x1`
+    a2_1: f32x4,
+    a2_2: f32x4,
+    ab: f32x4,
+    b: f32x4,
+    /// `[C, C·A]`, `[0, C·B]` and `D`, the outputs coming out as
+    /// `c_1·s1 + c_2·s2 + cb·x0 + d·[x0, x1]`
+    c_1: f32x4,
+    c_2: f32x4,
+    cb: f32x4,
+    d: f32x4,
+}
+
+impl Kernel {
+    fn new([g, k, m0, m1, m2]: Coefficients) -> Self {
+        let one = Simd::splat(1.);
+        let two = Simd::splat(2.);
+
+        let a1 = one / (one + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let [a11, a12, a21, a22] = [two * a1 - one, -two * a2, two * a2, one - two * a3];
+        let [b1, b2] = [two * a2, two * a3];
+        let [c1, c2] = [m1 * a1 + m2 * a2, m2 * (one - a3) - m1 * a2];
+        let d = m0 + m1 * a2 + m2 * a3;
+
+        Self {
+            a2_1: join(a11 * a11 + a12 * a21, a21 * a11 + a22 * a21),
+            a2_2: join(a11 * a12 + a12 * a22, a21 * a12 + a22 * a22),
+            ab: join(a11 * b1 + a12 * b2, a21 * b1 + a22 * b2),
+            b: join(b1, b2),
+            c_1: join(c1, c1 * a11 + c2 * a21),
+            c_2: join(c2, c1 * a12 + c2 * a22),
+            cb: join(Simd::splat(0.), c1 * b1 + c2 * b2),
+            d: twice(d),
+        }
+    }
+}
+
+/// The cascade's SVF, in single precision, the twin of `PreciseFilter`, whose coefficients
+/// ramp linearly. While they're static, it processes two stereo frames at a time, packed
+/// into `f32x4`s.
+#[derive(Default)]
+pub struct BatchFilter {
+    ic1: f32x2,
+    ic2: f32x2,
+    coeffs: Coefficients,
+    incs: Coefficients,
+    /// Built from `coeffs`, once they've stopped ticking
+    kernel: Option<Kernel>,
+}
+
+impl BatchFilter {
+    pub fn reset(&mut self) {
+        self.ic1 = Simd::splat(0.);
+        self.ic2 = Simd::splat(0.);
+    }
+
+    /// Jumps straight to the coefficients of `prototypes` at `w_c`.
+    pub fn update(&mut self, prototypes: [Prototype; 2], w_c: f32x2) {
+        self.coeffs = precise::coefficients(prototypes, w_c).map(|c| c.cast::<f32>());
+        self.incs = [Simd::splat(0.); 5];
+        self.kernel = None;
+    }
+
+    /// Ramps towards the coefficients of `prototypes` at `w_c`, by `inc` of the way per tick.
+    pub fn smoothing_update(&mut self, prototypes: [Prototype; 2], w_c: f32x2, inc: f32x2) {
+        let targets = precise::coefficients(prototypes, w_c).map(|c| c.cast::<f32>());
+
+        for ((inc_c, c), target) in self.incs.iter_mut().zip(self.coeffs).zip(targets) {
+            *inc_c = (target - c) * inc;
+        }
+    }
+
+    pub fn tick(&mut self) {
+        for (c, inc) in self.coeffs.iter_mut().zip(self.incs) {
+            *c += inc;
+        }
+        self.kernel = None;
+    }
+
+    pub fn process(&mut self, sample: f32x2) -> f32x2 {
+        let [g, k, m0, m1, m2] = self.coeffs;
+        let one = Simd::splat(1.);
+        let two = Simd::splat(2.);
+
+        let a1 = one / (one + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = sample - self.ic2;
+        let v1 = a1 * self.ic1 + a2 * v3;
+        let v2 = self.ic2 + a2 * self.ic1 + a3 * v3;
+        self.ic1 = two * v1 - self.ic1;
+        self.ic2 = two * v2 - self.ic2;
+
+        m0 * sample + m1 * v1 + m2 * v2
+    }
+
+    /// Filters `frames` in place, two at a time, without ticking. Same as calling `process`
+    /// on each of them.
+    pub fn process_block(&mut self, frames: &mut [f32x2]) {
+        let kernel = self.kernel.get_or_insert_with(|| Kernel::new(self.coeffs));
+        let mut pairs = frames.chunks_exact_mut(2);
+
+        for pair in &mut pairs {
+            let (x0, x1) = (pair[0], pair[1]);
+            let (s1, s2) = (twice(self.ic1), twice(self.ic2));
+            let x0x0 = twice(x0);
+
+            let y = kernel.c_1 * s1 + kernel.c_2 * s2 + kernel.cb * x0x0 + kernel.d * join(x0, x1);
+            let s = kernel.a2_1 * s1 + kernel.a2_2 * s2 + kernel.ab * x0x0 + kernel.b * twice(x1);
+
+            self.ic1 = simd_swizzle!(s, [0, 1]);
+            self.ic2 = simd_swizzle!(s, [2, 3]);
+            pair[0] = simd_swizzle!(y, [0, 1]);
+            pair[1] = simd_swizzle!(y, [2, 3]);
+        }
+
+        if let [frame] = pairs.into_remainder() {
+            *frame = self.process(*frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nih_plug::prelude::Enum;
+
+    use super::*;
+    use crate::{
+        biquad::analog_prototype, mode::Mode, res_to_damping, rng::Rng, sweep, BASE_SAMPLE_RATE,
+        TAU,
+    };
+
+    fn noise(len: usize) -> Vec<f32x2> {
+        let mut rng = Rng::new(11);
+        (0..len).map(|_| rng.next_f32x2()).collect()
+    }
+
+    #[test]
+    fn pairs_match_single_frames() {
+        let w_c = Simd::from_array([TAU * 1000., TAU * 3000.]) / Simd::splat(BASE_SAMPLE_RATE);
+        let input = noise(1001);
+
+        for mode in (0..Mode::variants().len()).map(Mode::from_index) {
+            let prototypes = [0.2, 1.].map(|k| analog_prototype(mode, k, 2.));
+            let mut filters: [BatchFilter; 2] = Default::default();
+            for filter in &mut filters {
+                filter.update(prototypes, w_c);
+                // ramping towards other settings over the first frames, then static
+                filter.smoothing_update(prototypes, w_c * Simd::splat(0.5), Simd::splat(0.01));
+            }
+            let [mut batched, mut single] = filters;
+
+            let mut frames = input.clone();
+            let (ramp, rest) = frames.split_at_mut(100);
+            for frame in ramp {
+                batched.tick();
+                *frame = batched.process(*frame);
+            }
+            let (first, second) = rest.split_at_mut(233);
+            batched.process_block(first);
+            batched.process_block(second);
+
+            for (n, (&x, frame)) in input.iter().zip(&frames).enumerate() {
+                if n < 100 {
+                    single.tick();
+                }
+                let expected = single.process(x);
+                let error = (*frame - expected).abs().reduce_max();
+                assert!(error < 1e-5, "{mode:?}[{n}]: {frame:?} != {expected:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn matches_the_plugins_filter() {
+        let w_c = TAU * 1000. / BASE_SAMPLE_RATE;
+        let input = noise(4096);
+        let left: Vec<f32> = input.iter().map(|x| x[0]).collect();
+
+        for mode in (0..Mode::variants().len()).map(Mode::from_index) {
+            let mut filter = BatchFilter::default();
+            filter.update(
+                [analog_prototype(mode, res_to_damping(0.5), 2.); 2],
+                Simd::splat(w_c),
+            );
+            let mut frames = input.clone();
+            filter.process_block(&mut frames);
+
+            let expected = sweep::render(mode.filter_mode(), w_c, 0.5, 2., &left);
+            for (n, (frame, expected)) in frames.iter().zip(expected).enumerate() {
+                assert!((frame[0] - expected).abs() < 1e-4, "{mode:?}[{n}]");
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::mode::Mode;
+
+/// Numerator and denominator of an analog, cutoff-normalized, second order
+/// transfer function, in ascending powers of `s`.
+pub type Prototype = ([f32; 3], [f32; 3]);
+
+/// The analog prototype of `mode`, `k` being the SVF's damping (twice the resonance)
+/// and `gain` the linear amplitude gain of the shelving and bell modes. Doesn't include
+/// the mode's output scale.
+pub fn analog_prototype(mode: Mode, k: f32, gain: f32) -> Prototype {
+    let den = [1., k, 1.];
+    let a = gain.sqrt();
+    let sqrt_a = a.sqrt();
+
+    match mode {
+        Mode::LowPass => ([1., 0., 0.], den),
+        Mode::BandPass => ([0., 1., 0.], den),
+        Mode::UnitBandPass => ([0., k, 0.], den),
+        Mode::HighPass => ([0., 0., 1.], den),
+        Mode::AllPass => ([1., -k, 1.], den),
+        Mode::Notch => ([1., 0., 1.], den),
+        Mode::LowShelf => ([a * a, a * sqrt_a * k, a], [1., sqrt_a * k, a]),
+        Mode::Bell => ([1., k * a, 1.], [1., k / a, 1.]),
+        Mode::HighShelf | Mode::Tilt => ([a, a * sqrt_a * k, a * a], [a, sqrt_a * k, 1.]),
+    }
+}
+
+/// Biquad coefficients `[b0, b1, b2, a1, a2]` (`a0` normalized to 1) of `mode` at
+/// the cutoff `w_c`, in radians per sample, through the (prewarped) bilinear transform.
+pub fn coefficients(mode: Mode, w_c: f32, k: f32, gain: f32) -> [f32; 5] {
+    let ([b0, b1, b2], [a0, a1, a2]) = analog_prototype(mode, k, gain);
+    let scale = mode.output_scale(gain);
+
+    // s = c * (1 - z^-1) / (1 + z^-1), with s = j at the cutoff
+    let c = 1. / f32::tan(w_c * 0.5);
+    let c2 = c * c;
+
+    let bilinear = |p0: f32, p1: f32, p2: f32| {
+        [
+            p0 + p1 * c + p2 * c2,
+            2. * (p0 - p2 * c2),
+            p0 - p1 * c + p2 * c2,
+        ]
+    };
+
+    let [n0, n1, n2] = bilinear(b0, b1, b2);
+    let [d0, d1, d2] = bilinear(a0, a1, a2);
+
+    let scale = scale / d0;
+    [n0 * scale, n1 * scale, n2 * scale, d1 / d0, d2 / d0]
+}
+
+#[cfg(test)]
+mod tests {
+    use nih_plug::prelude::Enum;
+    use num::Complex;
+
+    use super::*;
+    use crate::{transfer_function, BASE_SAMPLE_RATE, TAU};
+
+    #[test]
+    fn coefficients_match_the_transfer_function() {
+        let w_c = TAU * 1000. / BASE_SAMPLE_RATE;
+
+        for mode in (0..Mode::variants().len()).map(Mode::from_index) {
+            for res in [0.1, 0.5] {
+                let [b0, b1, b2, a1, a2] = coefficients(mode, w_c, 2. * res, 2.);
+
+                for w in [0.01, 0.1, w_c, 0.5, 1., 2., 3.] {
+                    let z1 = Complex::from_polar(1., -w);
+                    let z2 = z1 * z1;
+                    let biquad = (z1 * b1 + z2 * b2 + b0) / (z1 * a1 + z2 * a2 + 1.);
+
+                    let expected =
+                        transfer_function(mode.filter_mode(), 1, w, f32::tan(w_c * 0.5), res, 2.)
+                            * mode.output_scale(2.);
+                    let error = (biquad - expected).norm() / expected.norm().max(1.);
+                    assert!(
+                        error < 1e-3,
+                        "{mode:?}, res {res}, at {w}: {biquad} != {expected}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use plugin_util::simd::*;
+
+/// Accumulates the correlation between a stereo signal's two channels.
+#[derive(Default)]
+pub struct Correlation {
+    lr: f32,
+    ll: f32,
+    rr: f32,
+}
+
+impl Correlation {
+    pub fn push(&mut self, frame: f32x2) {
+        let [l, r] = frame.to_array();
+        self.lr += l * r;
+        self.ll += l * l;
+        self.rr += r * r;
+    }
+
+    /// From -1 (opposite channels) to 1 (identical channels), `None` for silence.
+    pub fn value(&self) -> Option<f32> {
+        let norm = (self.ll * self.rr).sqrt();
+        (norm > f32::MIN_POSITIVE).then(|| self.lr / norm)
+    }
+}
@@ -0,0 +1,212 @@
+use nih_plug::prelude::Enum;
+use plugin_util::simd::*;
+
+/// Amplitude of the noise added to the filter's input by `DenormalStrategy::Noise`,
+/// far below audibility but way above the denormal range.
+pub const ANTI_DENORMAL_NOISE: f32 = 1e-20;
+
+/// Below this (-140 dBFS), input and output count as silent.
+pub const SILENCE_LEVEL: f32 = 1e-7;
+
+/// Counts how long both the input and the output have been silent, so that tails can be
+/// cut to exact zero, instead of decaying forever through the denormal range (or, with
+/// `DenormalStrategy::Noise`, settling on the noise floor).
+#[derive(Default)]
+pub struct SilenceGate {
+    quiet_samples: usize,
+}
+
+impl SilenceGate {
+    pub fn reset(&mut self) {
+        self.quiet_samples = 0;
+    }
+
+    /// Whether the gate has been closed, by at least `hold` silent samples.
+    pub fn is_closed(&self, hold: usize) -> bool {
+        self.quiet_samples >= hold
+    }
+
+    /// Returns whether the gate just closed, after `hold` silent samples in a row.
+    pub fn process(&mut self, input: f32x2, output: f32x2, hold: usize) -> bool {
+        let level = Simd::splat(SILENCE_LEVEL);
+        let quiet = input.abs().simd_lt(level).all() && output.abs().simd_lt(level).all();
+
+        self.quiet_samples = if quiet {
+            self.quiet_samples.saturating_add(1)
+        } else {
+            0
+        };
+
+        self.quiet_samples == hold
+    }
+}
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DenormalStrategy {
+    #[default]
+    #[name = "Noise"]
+    Noise,
+    #[name = "FTZ/DAZ"]
+    FlushToZero,
+}
+
+/// Sets the CPU's flush-to-zero (and denormals-are-zero, where available) flags,
+/// restoring the previous ones on drop, so that other plugins on this thread aren't affected.
+/// A no-op with the `safe` feature, as it needs inline assembly.
+pub struct ScopedFtz {
+    #[cfg(all(
+        not(feature = "safe"),
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    previous: usize,
+}
+
+#[cfg(all(not(feature = "safe"), target_arch = "x86_64"))]
+const FTZ_FLAGS: usize = 0x8040; // FTZ (bit 15) | DAZ (bit 6) in MXCSR
+
+#[cfg(all(not(feature = "safe"), target_arch = "aarch64"))]
+const FTZ_FLAGS: usize = 1 << 24; // FZ in FPCR
+
+#[cfg(all(not(feature = "safe"), target_arch = "x86_64"))]
+fn get_flags() -> usize {
+    let mut csr = 0u32;
+    // SAFETY: only reads the SSE control register
+    unsafe {
+        core::arch::asm!("stmxcsr [{}]", in(reg) &mut csr, options(nostack, preserves_flags));
+    }
+    csr as usize
+}
+
+#[cfg(all(not(feature = "safe"), target_arch = "x86_64"))]
+fn set_flags(flags: usize) {
+    let csr = flags as u32;
+    // SAFETY: only changes floating point rounding/denormal behaviour
+    unsafe {
+        core::arch::asm!("ldmxcsr [{}]", in(reg) &csr, options(nostack, readonly, preserves_flags));
+    }
+}
+
+#[cfg(all(not(feature = "safe"), target_arch = "aarch64"))]
+fn get_flags() -> usize {
+    let fpcr: usize;
+    // SAFETY: only reads the floating point control register
+    unsafe {
+        core::arch::asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags));
+    }
+    fpcr
+}
+
+#[cfg(all(not(feature = "safe"), target_arch = "aarch64"))]
+fn set_flags(flags: usize) {
+    // SAFETY: only changes floating point rounding/denormal behaviour
+    unsafe {
+        core::arch::asm!("msr fpcr, {}", in(reg) flags, options(nomem, nostack, preserves_flags));
+    }
+}
+
+impl ScopedFtz {
+    #[cfg(all(
+        not(feature = "safe"),
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    pub fn enable() -> Self {
+        let previous = get_flags();
+        set_flags(previous | FTZ_FLAGS);
+        Self { previous }
+    }
+
+    #[cfg(any(
+        feature = "safe",
+        not(any(target_arch = "x86_64", target_arch = "aarch64"))
+    ))]
+    pub fn enable() -> Self {
+        Self {}
+    }
+}
+
+impl Drop for ScopedFtz {
+    fn drop(&mut self) {
+        #[cfg(all(
+            not(feature = "safe"),
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        ))]
+        set_flags(self.previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plugin_util::filter::svf::FilterMode;
+
+    use super::*;
+    use crate::{rng::Rng, Filter, BASE_SAMPLE_RATE, TAU};
+
+    /// A resonant lowpass's outputs, ringing out from a decaying exponential, with and without
+    /// the anti-denormal noise. `gate` cuts the tail as the plugin does.
+    fn ring_out(noise: bool, gate: bool) -> Vec<f32> {
+        let mut filter = Filter::default();
+        Filter::get_update_function(FilterMode::LP)(
+            &mut filter,
+            Simd::splat(TAU * 1000. / BASE_SAMPLE_RATE),
+            Simd::splat(0.2),
+            Simd::splat(1.),
+        );
+        let get_output = Filter::get_output_function(FilterMode::LP);
+        let mut rng = Rng::new(9);
+        let mut silence = SilenceGate::default();
+        let hold = 441;
+
+        (0..20000)
+            .map(|n| {
+                let mut input = Simd::splat(if n < 2000 {
+                    f32::exp(-(n as f32) / 100.)
+                } else {
+                    0.
+                });
+                let dry = input;
+                if noise {
+                    input += rng.next_f32x2() * Simd::splat(ANTI_DENORMAL_NOISE);
+                }
+
+                filter.process(input);
+                let output = get_output(&filter);
+                if gate && silence.process(dry, output, hold) {
+                    filter.reset();
+                }
+                output[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn noise_keeps_the_state_out_of_denormals() {
+        assert!(ring_out(false, false).iter().any(|x| x.is_subnormal()));
+        assert!(!ring_out(true, false).iter().any(|x| x.is_subnormal()));
+    }
+
+    #[test]
+    fn the_gate_flushes_the_tail_to_zero() {
+        let output = ring_out(false, true);
+        let silent_from = output.iter().rposition(|&x| x != 0.).unwrap() + 1;
+
+        // well before the tail would have reached the denormal range
+        assert!(silent_from < 5000, "{silent_from}");
+        assert!(output[..silent_from].iter().all(|x| !x.is_subnormal()));
+    }
+
+    #[cfg(all(
+        not(feature = "safe"),
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[test]
+    fn ftz_flushes_and_restores() {
+        use core::hint::black_box;
+
+        let tiny = black_box(f32::MIN_POSITIVE);
+        {
+            let _ftz = ScopedFtz::enable();
+            assert_eq!(black_box(tiny) * black_box(0.5), 0.);
+        }
+        assert!((black_box(tiny) * black_box(0.5)).is_subnormal());
+    }
+}
@@ -0,0 +1,54 @@
+use nih_plug::prelude::Enum;
+use plugin_util::simd::*;
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DriveCharacter {
+    /// symmetric, odd harmonics only
+    #[default]
+    #[name = "Clean"]
+    Clean,
+    /// negative half-waves clip harder than positive ones
+    #[name = "Tube"]
+    Tube,
+    /// soft knee, slightly biased
+    #[name = "Tape"]
+    Tape,
+}
+
+/// Rational approximation of `tanh`, exact at, and clamped beyond, ±3.
+pub fn tanh(x: f32x2) -> f32x2 {
+    let x = x.simd_clamp(Simd::splat(-3.), Simd::splat(3.));
+    let x2 = x * x;
+    x * (Simd::splat(27.) + x2) / (Simd::splat(27.) + Simd::splat(9.) * x2)
+}
+
+fn algebraic(x: f32x2) -> f32x2 {
+    x / (Simd::splat(1.) + x * x).sqrt()
+}
+
+impl DriveCharacter {
+    fn shape(self, x: f32x2) -> f32x2 {
+        match self {
+            Self::Clean => tanh(x),
+            Self::Tube => {
+                const NEGATIVE_HARDNESS: f32 = 1.6;
+                let negative = tanh(x * Simd::splat(NEGATIVE_HARDNESS))
+                    * Simd::splat(NEGATIVE_HARDNESS.recip());
+                x.simd_ge(Simd::splat(0.)).select(tanh(x), negative)
+            }
+            Self::Tape => {
+                // offset so that silence stays silent
+                const BIAS: f32 = 0.1;
+                let bias = Simd::splat(BIAS);
+                algebraic(x + bias) - algebraic(bias)
+            }
+        }
+    }
+
+    /// Saturates `sample` driven by the linear gain `drive`, scaled back down so that
+    /// low levels pass through at (roughly) unity gain. The curve is stretched so that it
+    /// starts bending at the linear level `headroom`, instead of at 1.
+    pub fn process(self, sample: f32x2, drive: f32, headroom: f32) -> f32x2 {
+        self.shape(sample * Simd::splat(drive / headroom)) * Simd::splat(headroom / drive)
+    }
+}
@@ -1,35 +1,940 @@
-use core::{cell::RefCell, sync::atomic::Ordering};
+use core::{
+    cell::{Cell, RefCell},
+    f32::consts::{FRAC_PI_2, LN_2, PI},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::sync::Mutex;
 
-use nih_plug::params::Param;
-use nih_plug_vizia::vizia::{prelude::*, vg};
+use nih_plug::{
+    params::Param,
+    prelude::{AsyncExecutor, AtomicF32, Enum, GuiContext, ParamPtr, Params, PluginState},
+};
+use nih_plug_vizia::{
+    vizia::{prelude::*, vg},
+    widgets::{RawParamEvent, ResizeHandle},
+    GuiContextEvent, ViziaState,
+};
 use num::Complex;
 use plugin_util::{
-    simd::f32x1,
+    simd::{f32x1, f32x2},
     smoothing::{LogSmoother, Smoother},
 };
 
-use crate::{Arc, Filter, SVFParams, BASE_SAMPLE_RATE, MAX_FREQ, MIN_FREQ, TAU};
+use crate::{
+    damping_to_res,
+    export::PlotSnapshot,
+    filter_bank::FilterBank,
+    matching::{self, Task},
+    mode::Mode,
+    phase_eq::PhaseEq,
+    phaser::Phaser,
+    presets::{Preset, PRESETS},
+    res_to_damping,
+    spectrum::{fft, SpectrumTap, SPECTRUM_LEN},
+    transfer_function, Arc, Filter, Quality, SVFFilter, SVFParams, MAX_RES, MIN_RES, SELF_OSC_RES,
+    TAU,
+};
+
+/// Half the vertical range of the plot, in dB, when not auto-scaling
+const DEFAULT_HALF_RANGE_DB: f32 = 35.;
+
+fn dashed_hline(path: &mut vg::Path, x_start: f32, x_end: f32, y: f32, dash: f32) {
+    let mut x = x_start;
+    while x < x_end {
+        path.move_to(x, y);
+        path.line_to((x + dash).min(x_end), y);
+        x += 2. * dash;
+    }
+}
+
+#[derive(Params)]
+pub struct EditorParams {
+    #[persist = "editor_state"]
+    pub vizia_state: Arc<ViziaState>,
+    /// The window's size, read by `vizia_state` whenever the editor opens or gets resized
+    #[persist = "window_size"]
+    window_size: Arc<Mutex<(u32, u32)>>,
+    /// Whether the editor shows a live spectrum under the response
+    #[persist = "split_view"]
+    split_view: AtomicBool,
+    /// Fraction of the editor's height taken by the response, in split view
+    #[persist = "split_ratio"]
+    split_ratio: AtomicF32,
+    /// `(frequency (Hz), gain (dB))` points, overlaid on the response, and matched against
+    #[persist = "target_curve"]
+    pub target_curve: Mutex<Vec<(f32, f32)>>,
+    /// The zoomed-in part of the frequency axis, as fractions of the full (logarithmic) axis
+    #[persist = "zoom_start"]
+    zoom_start: AtomicF32,
+    #[persist = "zoom_end"]
+    zoom_end: AtomicF32,
+    /// The frequency window, in Hz, left undimmed in the response, none if both are 0
+    #[persist = "focus_low"]
+    focus_low: AtomicF32,
+    #[persist = "focus_high"]
+    focus_high: AtomicF32,
+    /// A second mode whose response is drawn, dimmed, next to the current one's
+    compare_mode: Mutex<Option<Mode>>,
+    /// The response as last drawn, for exporting
+    pub plot_snapshot: Mutex<PlotSnapshot>,
+    /// The spectra shown, analyzed on the editor's timer
+    spectra: Mutex<Spectra>,
+    /// Lowest and highest internal cutoff modulation seen lately, in octaves
+    mod_range: Mutex<(f32, f32)>,
+    /// The state as of the last load, tagged with `SVFParams::state_generation`. Captured
+    /// by the editor, so tweaks made before it first opens are part of it.
+    loaded_state: Mutex<Option<(u32, PluginState)>>,
+}
+
+impl EditorParams {
+    fn zoom(&self) -> (f32, f32) {
+        (
+            self.zoom_start.load(Ordering::Relaxed),
+            self.zoom_end.load(Ordering::Relaxed),
+        )
+    }
+
+    fn set_zoom(&self, start: f32, end: f32) {
+        self.zoom_start.store(start, Ordering::Relaxed);
+        self.zoom_end.store(end, Ordering::Relaxed);
+    }
+
+    fn focus(&self) -> Option<(f32, f32)> {
+        let (low, high) = (
+            self.focus_low.load(Ordering::Relaxed),
+            self.focus_high.load(Ordering::Relaxed),
+        );
+        (low < high).then_some((low, high))
+    }
+
+    fn set_focus(&self, a: f32, b: f32) {
+        self.focus_low.store(a.min(b), Ordering::Relaxed);
+        self.focus_high.store(a.max(b), Ordering::Relaxed);
+    }
+
+    /// Widens `mod_range` to the internal modulation of the cutoff, `octaves`, its extremes
+    /// slowly drifting back towards it. Returns whether the modulation needs to be shown.
+    fn follow_modulation(&self, octaves: f32) -> bool {
+        const MOD_RANGE_DECAY: f32 = 0.02;
+
+        let mut mod_range = self.mod_range.lock().unwrap();
+        let (mod_min, mod_max) = *mod_range;
+        *mod_range = (
+            (mod_min + MOD_RANGE_DECAY).min(octaves),
+            (mod_max - MOD_RANGE_DECAY).max(octaves),
+        );
+
+        octaves != 0. || mod_range.0 != mod_range.1
+    }
+
+    /// A size comfortably fitting the toolbar, the plot's readouts, and the spectrum if shown.
+    fn fit_size(&self) -> (u32, u32) {
+        const CHAR_WIDTH: f32 = 7.;
+        const BUTTON_PADDING: f32 = 24.;
+
+        let labels = TOOLBAR_LABELS
+            .iter()
+            .copied()
+            .chain(PRESETS.iter().map(|preset| preset.name));
+        let toolbar_width = labels
+            .map(|label| label.len() as f32 * CHAR_WIDTH + BUTTON_PADDING)
+            .sum::<f32>();
+
+        let height = if self.split_view.load(Ordering::Relaxed) {
+            PLOT_HEIGHT / self.split_ratio.load(Ordering::Relaxed)
+        } else {
+            PLOT_HEIGHT
+        };
+
+        (
+            toolbar_width.max(DEFAULT_SIZE.0 as f32) as u32,
+            height as u32,
+        )
+    }
+
+    /// Steps the comparison through every mode, then back to none.
+    fn cycle_compare_mode(&self) {
+        let mut compare_mode = self.compare_mode.lock().unwrap();
+        let next = compare_mode.map_or(0, |mode| mode.to_index() + 1);
+        *compare_mode = (next < Mode::variants().len()).then(|| Mode::from_index(next));
+    }
+}
+
+impl Default for EditorParams {
+    fn default() -> Self {
+        let window_size = Arc::new(Mutex::new(DEFAULT_SIZE));
+        let size = window_size.clone();
+
+        Self {
+            vizia_state: ViziaState::new(move || *size.lock().unwrap()),
+            window_size,
+            split_view: AtomicBool::new(false),
+            split_ratio: AtomicF32::new(0.6),
+            target_curve: Mutex::default(),
+            zoom_start: AtomicF32::new(0.),
+            zoom_end: AtomicF32::new(1.),
+            focus_low: AtomicF32::new(0.),
+            focus_high: AtomicF32::new(0.),
+            compare_mode: Mutex::default(),
+            plot_snapshot: Mutex::default(),
+            spectra: Mutex::default(),
+            mod_range: Mutex::default(),
+            loaded_state: Mutex::default(),
+        }
+    }
+}
+
+const DEFAULT_SIZE: (u32, u32) = (400, 140);
+
+/// How often the editor polls the audio thread's side of the plot, and re-analyzes the spectra
+const REFRESH_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Size of the plot's text, in logical pixels, at the default window size
+const LABEL_FONT_SIZE: f32 = 10.;
+
+/// Height needed by the response and its readouts, split view aside
+const PLOT_HEIGHT: f32 = 180.;
+
+/// The toolbar's buttons, bar the presets, for sizing the window to fit them
+const TOOLBAR_LABELS: &[&str] = &[
+    "Ping",
+    "Audition",
+    "Split",
+    "Paste Curve",
+    "Unzoom",
+    "Revert",
+    "Compare",
+    "Export",
+    "Quality",
+    "Fit",
+    "Match",
+];
+
+/// Density of the plot's curve, larger windows getting smoother ones
+const POINTS_PER_PIXEL: f32 = 1.75;
+const HIGH_RES_POINTS_PER_PIXEL: f32 = 10.;
+const MIN_POINTS: f32 = 100.;
+
+/// Narrowest zoom window, as a fraction of the full frequency axis
+const MIN_ZOOM_SPAN: f32 = 0.05;
+
+const MIN_SPLIT_RATIO: f32 = 0.2;
+const MAX_SPLIT_RATIO: f32 = 0.8;
+
+/// Frequency range of the plots' x-axis, when fixed independently of the sample rate
+const FIXED_AXIS: (f32, f32) = (20., 20000.);
+
+/// The lowest and highest frequency of the (log-frequency) x-axis shared by all plots.
+fn freq_axis(params: &SVFParams) -> (f32, f32) {
+    let (min, max) = if params.display.fixed_axis.value() {
+        FIXED_AXIS
+    } else {
+        (
+            params.cutoff_range.min_hz,
+            params.max_freq.load(Ordering::Relaxed),
+        )
+    };
+
+    let (start, end) = params.editor.zoom();
+    let ratio = max / min;
+    (min * ratio.powf(start), min * ratio.powf(end))
+}
+
+/// Lowest level, in dBFS, shown by the spectrum view
+const SPECTRUM_FLOOR_DB: f32 = -90.;
+const SPECTRUM_NUM_BINS: usize = 256;
+
+/// A spectrum, in dB per bin, spread over the same log-frequency axis as the response,
+/// averaged over the previous frames.
+struct Spectrum {
+    average: [f32; SPECTRUM_NUM_BINS],
+    /// The number of bins below nyquist
+    num_bins: usize,
+}
+
+impl Default for Spectrum {
+    fn default() -> Self {
+        Self {
+            average: [SPECTRUM_FLOOR_DB; SPECTRUM_NUM_BINS],
+            num_bins: 0,
+        }
+    }
+}
+
+impl Spectrum {
+    fn levels(&self) -> &[f32] {
+        &self.average[..self.num_bins]
+    }
+}
+
+/// The output's and input's spectra, analyzed on the editor's timer rather than when
+/// drawing, so that they keep moving while nothing else asks for a redraw.
+struct Spectra {
+    samples: Box<[f32; SPECTRUM_LEN]>,
+    bins: Box<[Complex<f32>; SPECTRUM_LEN]>,
+    output: Spectrum,
+    input: Spectrum,
+}
+
+impl Default for Spectra {
+    fn default() -> Self {
+        Self {
+            samples: Box::new([0.; SPECTRUM_LEN]),
+            bins: Box::new([Complex::new(0., 0.); SPECTRUM_LEN]),
+            output: Spectrum::default(),
+            input: Spectrum::default(),
+        }
+    }
+}
+
+impl Spectra {
+    /// Analyzes the latest samples of whichever spectra are shown. Returns whether any is.
+    fn update(&mut self, params: &SVFParams) -> bool {
+        let show_output = params.editor.split_view.load(Ordering::Relaxed);
+        let show_input = params.display.input_spectrum.value();
+
+        if show_output {
+            self.analyze(&params.spectrum, params, |spectra| &mut spectra.output);
+        }
+        if show_input {
+            self.analyze(&params.input_spectrum, params, |spectra| &mut spectra.input);
+        }
+
+        show_output || show_input
+    }
+
+    /// Updates the spectrum picked by `spectrum` with that of `tap`'s latest samples.
+    fn analyze(
+        &mut self,
+        tap: &SpectrumTap,
+        params: &SVFParams,
+        spectrum: impl Fn(&mut Self) -> &mut Spectrum,
+    ) {
+        let (axis_min, axis_max) = freq_axis(params);
+
+        tap.snapshot(&mut self.samples);
+
+        // hann window
+        let mut window_sum = 0.;
+        for (i, (bin, &sample)) in self.bins.iter_mut().zip(self.samples.iter()).enumerate() {
+            let window = 0.5 - 0.5 * f32::cos(TAU * i as f32 / SPECTRUM_LEN as f32);
+            *bin = Complex::new(sample * window, 0.);
+            window_sum += window;
+        }
+
+        fft(&mut self.bins[..]);
+
+        let two_pi_tick = params.two_pi_tick.load(Ordering::Relaxed);
+        let nyquist = TAU / (2. * two_pi_tick);
+        // the (fractional) FFT bin of `freq`
+        let fft_bin = |freq: f32| freq * two_pi_tick / TAU * SPECTRUM_LEN as f32;
+        let bin_magnitude = |bin: usize| 2. * self.bins[bin].norm() / window_sum;
+
+        // bins are spread over the same log-frequency axis as the response
+        let bin_freq = |bin: usize| {
+            let x_norm = bin as f32 / (SPECTRUM_NUM_BINS - 1) as f32;
+            axis_min * (axis_max / axis_min).powf(x_norm)
+        };
+        let num_bins = (0..SPECTRUM_NUM_BINS)
+            .position(|bin| bin_freq(bin) >= nyquist)
+            .unwrap_or(SPECTRUM_NUM_BINS);
+
+        let mut levels = [SPECTRUM_FLOOR_DB; SPECTRUM_NUM_BINS];
+
+        for (bin, level) in levels[..num_bins].iter_mut().enumerate() {
+            let (start, end) = (fft_bin(bin_freq(bin)), fft_bin(bin_freq(bin + 1)));
+
+            // interpolated between the FFT's bins where they're sparser than the plot's,
+            // the loudest of those it spans otherwise
+            let magnitude = if end - start <= 1. {
+                let below = start as usize;
+                let above = (below + 1).min(SPECTRUM_LEN / 2);
+                let t = start.fract();
+                bin_magnitude(below) + (bin_magnitude(above) - bin_magnitude(below)) * t
+            } else {
+                let last = (end as usize).min(SPECTRUM_LEN / 2);
+                (start.ceil() as usize..=last)
+                    .map(bin_magnitude)
+                    .fold(0., f32::max)
+            };
+
+            *level = (20. * magnitude.max(1e-9).log10()).max(SPECTRUM_FLOOR_DB);
+        }
+
+        // average over up to a third of an octave around each bin, then over time
+
+        const MAX_SMOOTHING_OCTAVES: f32 = 1. / 3.;
+        const MAX_TIME_SMOOTHING: f32 = 0.9;
+        let smoothing = params.display.spectrum_smoothing.value();
+
+        let bins_per_octave = (SPECTRUM_NUM_BINS - 1) as f32 / (axis_max / axis_min).log2();
+        let half_width = (0.5 * smoothing * MAX_SMOOTHING_OCTAVES * bins_per_octave) as usize;
+        let time_coeff = smoothing * MAX_TIME_SMOOTHING;
+
+        let spectrum = spectrum(self);
+        spectrum.num_bins = num_bins;
+
+        for bin in 0..num_bins {
+            let neighbours =
+                &levels[bin.saturating_sub(half_width)..(bin + half_width + 1).min(num_bins)];
+            let level = neighbours.iter().sum::<f32>() / neighbours.len() as f32;
+
+            spectrum.average[bin] = level + (spectrum.average[bin] - level) * time_coeff;
+        }
+    }
+}
+
+pub fn create(
+    cx: &mut Context,
+    params: Arc<SVFParams>,
+    executor: AsyncExecutor<SVFFilter>,
+    gui_context: Arc<dyn GuiContext>,
+) {
+    // the audio thread's side of the plot (the sample rate, the modulations, the spectra)
+    // moves without any parameter changing
+    let tick_params = params.clone();
+    let tick_context = gui_context.clone();
+    let last_audio_state = Cell::new(None);
+    let timer = cx.add_timer(REFRESH_INTERVAL, None, move |cx, action| {
+        if let TimerAction::Tick(_) = action {
+            let audio_state = Some((
+                tick_params.two_pi_tick.load(Ordering::Relaxed),
+                tick_params.phaser_sweep.load(Ordering::Relaxed),
+                tick_params.coefficients.load(),
+            ));
+            let mut redraw = last_audio_state.replace(audio_state) != audio_state;
+
+            let editor = &tick_params.editor;
+            redraw |= editor.follow_modulation(tick_params.cutoff_mod.load(Ordering::Relaxed));
+            redraw |= editor.spectra.lock().unwrap().update(&tick_params);
+
+            if redraw {
+                cx.needs_redraw();
+            }
+
+            let generation = tick_params.state_generation.load(Ordering::Relaxed);
+            let mut loaded_state = tick_params.editor.loaded_state.lock().unwrap();
+            if loaded_state.as_ref().map(|&(loaded, _)| loaded) != Some(generation) {
+                *loaded_state = Some((generation, tick_context.get_state()));
+            }
+        }
+    });
+    cx.start_timer(timer);
+
+    ZStack::new(cx, move |cx| {
+        SVFBode::new(cx, params.clone());
+
+        HStack::new(cx, move |cx| {
+            let ping_params = params.clone();
+            Button::new(
+                cx,
+                move |_| ping_params.ping.store(true, Ordering::Relaxed),
+                |cx| Label::new(cx, "Ping"),
+            )
+            .size(Auto);
+
+            let audition_params = params.clone();
+            Button::new(
+                cx,
+                move |_| audition_params.audition.store(true, Ordering::Relaxed),
+                |cx| Label::new(cx, "Audition"),
+            )
+            .size(Auto);
+
+            let split_params = params.clone();
+            Button::new(
+                cx,
+                move |cx| {
+                    split_params
+                        .editor
+                        .split_view
+                        .fetch_xor(true, Ordering::Relaxed);
+                    cx.needs_redraw();
+                },
+                |cx| Label::new(cx, "Split"),
+            )
+            .size(Auto);
+
+            // the target curve is pasted as text, see `matching::parse_curve`
+            let paste_params = params.clone();
+            Button::new(
+                cx,
+                move |cx| {
+                    if let Ok(text) = cx.get_clipboard() {
+                        *paste_params.editor.target_curve.lock().unwrap() =
+                            matching::parse_curve(&text);
+                        cx.needs_redraw();
+                    }
+                },
+                |cx| Label::new(cx, "Paste Curve"),
+            )
+            .size(Auto);
+
+            let zoom_params = params.clone();
+            Button::new(
+                cx,
+                move |cx| {
+                    zoom_params.editor.set_zoom(0., 1.);
+                    cx.needs_redraw();
+                },
+                |cx| Label::new(cx, "Unzoom"),
+            )
+            .size(Auto);
+
+            for preset in PRESETS {
+                let preset_params = params.clone();
+                Button::new(
+                    cx,
+                    move |cx| apply_preset(cx, &preset_params, preset),
+                    |cx| Label::new(cx, preset.name),
+                )
+                .size(Auto);
+            }
+
+            // reloading the state re-initializes the plugin, the timer then captures it again
+            let revert_params = params.clone();
+            let revert_context = gui_context.clone();
+            Button::new(
+                cx,
+                move |_| {
+                    let loaded_state = revert_params.editor.loaded_state.lock().unwrap().take();
+                    if let Some((_, state)) = loaded_state {
+                        revert_context.set_state(state);
+                    }
+                },
+                |cx| Label::new(cx, "Revert"),
+            )
+            .size(Auto);
+
+            let compare_params = params.clone();
+            Button::new(
+                cx,
+                move |cx| {
+                    compare_params.editor.cycle_compare_mode();
+                    cx.needs_redraw();
+                },
+                |cx| Label::new(cx, "Compare"),
+            )
+            .size(Auto);
+
+            let export_params = params.clone();
+            let export_executor = executor.clone();
+            Button::new(
+                cx,
+                move |_| {
+                    export_executor.execute_background(Task::ExportPlot {
+                        params: export_params.clone(),
+                    })
+                },
+                |cx| Label::new(cx, "Export"),
+            )
+            .size(Auto);
+
+            // steps through the quality levels, `Custom` included
+            let quality_params = params.clone();
+            Button::new(
+                cx,
+                move |cx| {
+                    let quality = &quality_params.quality;
+                    let next = (quality.value().to_index() + 1) % Quality::variants().len();
+                    set_params(
+                        cx,
+                        &[(
+                            quality.as_ptr(),
+                            quality.preview_normalized(Quality::from_index(next)),
+                        )],
+                    );
+                },
+                |cx| Label::new(cx, "Quality"),
+            )
+            .size(Auto);
+
+            let fit_params = params.clone();
+            Button::new(
+                cx,
+                move |cx| {
+                    let size = fit_params.editor.fit_size();
+                    *fit_params.editor.window_size.lock().unwrap() = size;
+                    cx.emit(GuiContextEvent::Resize);
+                },
+                |cx| Label::new(cx, "Fit"),
+            )
+            .size(Auto);
+
+            Button::new(
+                cx,
+                move |_| {
+                    executor.execute_background(Task::MatchCurve {
+                        params: params.clone(),
+                        executor: executor.clone(),
+                        gui_context: gui_context.clone(),
+                    })
+                },
+                |cx| Label::new(cx, "Match"),
+            )
+            .size(Auto);
+        })
+        .size(Auto)
+        .col_between(Pixels(4.))
+        .left(Pixels(4.))
+        .top(Pixels(4.));
+
+        // scales the whole window, the scale being saved along with the editor's state
+        ResizeHandle::new(cx);
+    });
+}
+
+/// Sets every parameter in `changes` to its normalized value, as a single gesture, so that
+/// hosts record (and undo) them together. Every gesture is begun and ended within the same
+/// call, none can be left hanging by an interrupted interaction.
+fn set_params(cx: &mut EventContext, changes: &[(ParamPtr, f32)]) {
+    for &(param, _) in changes {
+        cx.emit(RawParamEvent::BeginSetParameter(param));
+    }
+    for &(param, normalized) in changes {
+        cx.emit(RawParamEvent::SetParameterNormalized(param, normalized));
+    }
+    for &(param, _) in changes {
+        cx.emit(RawParamEvent::EndSetParameter(param));
+    }
+}
+
+fn apply_preset(cx: &mut EventContext, params: &SVFParams, preset: &Preset) {
+    let cutoff = params.cutoff_range.normalized(preset.cutoff_hz);
+
+    set_params(
+        cx,
+        &[
+            (
+                params.mode.as_ptr(),
+                params.mode.preview_normalized(preset.mode),
+            ),
+            (params.cutoff.as_ptr(), cutoff),
+            (params.res.as_ptr(), params.res_normalized(preset.res)),
+            (
+                params.gain.as_ptr(),
+                params.gain.preview_normalized(preset.gain_db),
+            ),
+        ],
+    );
+}
+
+/// The cutoff-normalized (prewarped) frequency of the resonance peak of `mode`, if it has
+/// one, from `H(s) = N(s) / (s^2 + 2 * res * s + 1)`. A cascade of identical stages peaks
+/// at the same frequency.
+fn resonance_peak(mode: Mode, res: f32) -> Option<f32> {
+    match mode {
+        // no peak above critical damping
+        Mode::LowPass if res * res < 0.5 => Some((1. - 2. * res * res).sqrt()),
+        Mode::HighPass if res * res < 0.5 => Some((1. - 2. * res * res).sqrt().recip()),
+        Mode::BandPass => Some(1.),
+        _ => None,
+    }
+}
+
+/// Draws a small, idealized response of `mode` (moderate resonance, +12 dB gain) in
+/// the given box, spanning 3 octaves on either side of the cutoff.
+fn draw_mode_icon(canvas: &mut Canvas, mode: Mode, x: f32, y: f32, w: f32, h: f32) {
+    const ICON_POINTS: usize = 32;
+    const ICON_RANGE_DB: f32 = 18.;
+
+    let transfer_function = Filter::get_transfer_function::<f32>(mode.filter_mode());
+    let gain = 10f32.powf(12. / 20.);
+
+    let mut icon = vg::Path::new();
+
+    for i in 0..ICON_POINTS {
+        let t = i as f32 / (ICON_POINTS - 1) as f32;
+        let w_norm = (6. * t - 3.).exp2();
+
+        let response =
+            transfer_function(Complex::new(0., w_norm), 0.5, gain) * mode.output_scale(gain);
+        let gain_db = (10. * f32::log10(response.norm_sqr())).clamp(-ICON_RANGE_DB, ICON_RANGE_DB);
+
+        let point = (x + t * w, y + h / 2. - gain_db / ICON_RANGE_DB * h / 2.);
+
+        if i == 0 {
+            icon.move_to(point.0, point.1);
+        } else {
+            icon.line_to(point.0, point.1);
+        }
+    }
+
+    canvas.stroke_path(
+        &icon,
+        &vg::Paint::color(vg::Color::rgba(255, 255, 255, 200))
+            .with_line_width(1.5)
+            .with_anti_alias(true),
+    );
+}
+
+/// The response at one of the plotted frequencies
+struct PlotPoint {
+    x: f32,
+    freq: f32,
+    gain_db: f32,
+    phase: f32,
+    /// left and right channel gains, if they differ
+    spread: Option<(f32, f32)>,
+}
 
 pub struct SVFBode {
     params: Arc<SVFParams>,
-    phase_color_buffer: RefCell<Vec<vg::Color>>,
+    points: RefCell<Vec<PlotPoint>>,
+    dragging_split: bool,
+    /// Where the focus window being dragged started, in Hz
+    focus_drag: Option<f32>,
+    /// Factor applied to `LABEL_FONT_SIZE`, for the display's DPI and the window's size
+    text_scale: Cell<f32>,
+    /// The mouse's horizontal position, while it's over the plot
+    hover_x: Option<f32>,
 }
 
 impl SVFBode {
     pub fn new(cx: &mut Context, params: Arc<SVFParams>) -> Handle<Self> {
         SVFBode {
             params,
-            phase_color_buffer: Default::default(),
+            points: Default::default(),
+            dragging_split: false,
+            focus_drag: None,
+            text_scale: Cell::new(1.),
+            hover_x: None,
         }
         .build(cx, |_| ())
     }
+
+    fn default_of<P: Param>(param: &P) -> (ParamPtr, f32) {
+        (param.as_ptr(), param.default_normalized_value())
+    }
+
+    /// Ends whatever drag is ongoing, e.g. when the window loses the mouse mid-drag.
+    fn end_drags(&mut self, cx: &mut EventContext) {
+        if self.dragging_split || self.focus_drag.is_some() {
+            self.dragging_split = false;
+            self.focus_drag = None;
+            cx.release();
+        }
+    }
+
+    fn freq_axis(&self) -> (f32, f32) {
+        freq_axis(&self.params)
+    }
+
+    /// The frequency at the horizontal position `x`, the inverse of the plots' log-x mapping.
+    fn x_to_freq(&self, bounds: BoundingBox, x: f32) -> f32 {
+        let (axis_min, axis_max) = self.freq_axis();
+        let t = ((x - bounds.x) / bounds.w).clamp(0., 1.);
+        axis_min * (axis_max / axis_min).powf(t)
+    }
+
+    fn split_view(&self) -> bool {
+        self.params.editor.split_view.load(Ordering::Relaxed)
+    }
+
+    fn split_ratio(&self) -> f32 {
+        self.params.editor.split_ratio.load(Ordering::Relaxed)
+    }
 }
 
 impl View for SVFBode {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| {
+            let bounds = cx.bounds();
+            let split_y = bounds.y + bounds.h * self.split_ratio();
+
+            match *window_event {
+                WindowEvent::MouseDown(MouseButton::Left)
+                    if self.split_view() && (cx.mouse().cursory - split_y).abs() < 4. =>
+                {
+                    self.dragging_split = true;
+                    cx.capture();
+                    meta.consume();
+                }
+                WindowEvent::MouseMove(_, y) if self.dragging_split => {
+                    let ratio = ((y - bounds.y) / bounds.h).clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO);
+                    self.params
+                        .editor
+                        .split_ratio
+                        .store(ratio, Ordering::Relaxed);
+                    cx.needs_redraw();
+                    meta.consume();
+                }
+                WindowEvent::MouseUp(MouseButton::Left) if self.dragging_split => {
+                    self.dragging_split = false;
+                    cx.release();
+                    meta.consume();
+                }
+                // shift + drag picks the focus window, shift + click clears it
+                WindowEvent::MouseDown(MouseButton::Left)
+                    if cx.modifiers().contains(Modifiers::SHIFT) =>
+                {
+                    let freq = self.x_to_freq(bounds, cx.mouse().cursorx);
+                    self.focus_drag = Some(freq);
+                    self.params.editor.set_focus(freq, freq);
+                    cx.capture();
+                    cx.needs_redraw();
+                    meta.consume();
+                }
+                WindowEvent::MouseMove(x, _) => {
+                    self.hover_x = Some(x);
+                    cx.needs_redraw();
+
+                    if let Some(start) = self.focus_drag {
+                        let freq = self.x_to_freq(bounds, x);
+                        self.params.editor.set_focus(start, freq);
+                        meta.consume();
+                    }
+                }
+                WindowEvent::MouseLeave => {
+                    self.hover_x = None;
+                    cx.needs_redraw();
+                }
+                WindowEvent::MouseUp(MouseButton::Left) if self.focus_drag.is_some() => {
+                    self.focus_drag = None;
+                    cx.release();
+                    meta.consume();
+                }
+                // ctrl + scroll zooms around the cursor
+                WindowEvent::MouseScroll(_, delta) if cx.modifiers().contains(Modifiers::CTRL) => {
+                    let (start, end) = self.params.editor.zoom();
+                    let t = ((cx.mouse().cursorx - bounds.x) / bounds.w).clamp(0., 1.);
+                    let center = start + t * (end - start);
+
+                    let span = ((end - start) * 0.9f32.powf(delta)).clamp(MIN_ZOOM_SPAN, 1.);
+                    let start = (center - t * span).clamp(0., 1. - span);
+
+                    self.params.editor.set_zoom(start, start + span);
+                    cx.needs_redraw();
+                    meta.consume();
+                }
+                WindowEvent::MouseDoubleClick(MouseButton::Left) => {
+                    set_params(
+                        cx,
+                        &[
+                            Self::default_of(&self.params.cutoff),
+                            Self::default_of(&self.params.res),
+                        ],
+                    );
+                    meta.consume();
+                }
+                WindowEvent::MouseCaptureOutEvent => self.end_drags(cx),
+                _ => (),
+            }
+        });
+    }
+
     fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
         let bounds = cx.bounds();
+
+        // bounds are in physical pixels, text grows a little with the window, past its default size
+        let dpi = cx.scale_factor();
+        let growth = (bounds.h / (DEFAULT_SIZE.1 as f32 * dpi))
+            .sqrt()
+            .clamp(1., 1.5);
+        self.text_scale.set(dpi * growth);
+
+        if !self.split_view() {
+            self.draw_response(canvas, bounds);
+            return;
+        }
+
+        let response_h = bounds.h * self.split_ratio();
+        let response = BoundingBox {
+            h: response_h,
+            ..bounds
+        };
+        let spectrum = BoundingBox {
+            y: bounds.y + response_h,
+            h: bounds.h - response_h,
+            ..bounds
+        };
+
+        self.draw_response(canvas, response);
+        self.draw_spectrum(canvas, spectrum);
+
+        let mut divider = vg::Path::new();
+        divider.move_to(bounds.x, spectrum.y);
+        divider.line_to(bounds.x + bounds.w, spectrum.y);
+
+        canvas.stroke_path(
+            &divider,
+            &vg::Paint::color(vg::Color::rgba(255, 255, 255, 128)).with_line_width(2.),
+        );
+    }
+}
+
+impl SVFBode {
+    /// Anti-aliased paint for the plot's labels and readouts.
+    fn label_paint(&self, color: vg::Color) -> vg::Paint {
+        vg::Paint::color(color)
+            .with_font_size(LABEL_FONT_SIZE * self.text_scale.get())
+            .with_anti_alias(true)
+    }
+
+    fn line_height(&self) -> f32 {
+        1.2 * LABEL_FONT_SIZE * self.text_scale.get()
+    }
+
+    /// Gridlines at every decade of the frequency axis and every 10 dB (3 dB when zoomed in
+    /// closely), labelled along the bottom and the left edge.
+    fn draw_grid(
+        &self,
+        canvas: &mut Canvas,
+        bounds: BoundingBox,
+        freq_to_x: impl Fn(f32) -> f32,
+        db_to_y: impl Fn(f32) -> f32,
+        (center_db, half_range_db): (f32, f32),
+    ) {
+        let (axis_min, axis_max) = self.freq_axis();
+        let mut grid = vg::Path::new();
+        let label_paint = self.label_paint(vg::Color::rgba(255, 255, 255, 110));
+
+        let mut decade = 10f32.powf(axis_min.log10().ceil());
+        while decade <= axis_max {
+            let x = freq_to_x(decade);
+            grid.move_to(x, bounds.y);
+            grid.line_to(x, bounds.y + bounds.h);
+
+            let label = if decade >= 1000. {
+                format!("{}k", decade / 1000.)
+            } else {
+                format!("{decade}")
+            };
+            let _ = canvas.fill_text(
+                x + 3.,
+                bounds.y + bounds.h - 6. - self.line_height(),
+                label,
+                &label_paint,
+            );
+
+            decade *= 10.;
+        }
+
+        let step = if half_range_db > 15. { 10. } else { 3. };
+        let mut db = step * ((center_db - half_range_db) / step).ceil();
+        while db <= center_db + half_range_db {
+            let y = db_to_y(db);
+            grid.move_to(bounds.x, y);
+            grid.line_to(bounds.x + bounds.w, y);
+
+            let _ = canvas.fill_text(bounds.x + 3., y - 2., format!("{db:+}"), &label_paint);
+
+            db += step;
+        }
+
+        canvas.stroke_path(
+            &grid,
+            &vg::Paint::color(vg::Color::rgba(255, 255, 255, 32)).with_line_width(1.),
+        );
+    }
+
+    fn draw_response(&self, canvas: &mut Canvas, bounds: BoundingBox) {
         let width = bounds.width();
 
+        canvas.save();
+        canvas.scissor(bounds.x, bounds.y, bounds.w, bounds.h);
+
         // draw background
 
         let mut bg = vg::Path::new();
@@ -40,60 +945,209 @@ impl View for SVFBode {
 
         // draw bode plot
 
-        const NUM_POINTS: usize = 700;
+        // the high resolution plot also evaluates the main curve in double precision
+        let high_res = self.params.quality_settings().high_res_plot;
+        let points_per_pixel = if high_res {
+            HIGH_RES_POINTS_PER_PIXEL
+        } else {
+            POINTS_PER_PIXEL
+        };
+        let num_points = (width * points_per_pixel).max(MIN_POINTS) as usize;
 
-        let delta_x = bounds.width() / NUM_POINTS as f32;
+        let delta_x = bounds.width() / num_points as f32;
 
         let mut smoother = LogSmoother::<1>::default();
-        smoother.set_instantly(f32x1::from([MIN_FREQ]));
+        let (axis_min, axis_max) = self.freq_axis();
+        let freq_to_x =
+            |freq: f32| bounds.x + width * (freq / axis_min).ln() / (axis_max / axis_min).ln();
+
+        smoother.set_instantly(f32x1::from([axis_min]));
         smoother.set_increment(
-            f32x1::from([MAX_FREQ]),
-            f32x1::from([1. / NUM_POINTS as f32]),
+            f32x1::from([axis_max]),
+            f32x1::from([1. / num_points as f32]),
         );
 
         let (mut x, y) = bounds.center_left();
 
-        let mut plot = vg::Path::new();
-
-        plot.move_to(x, y);
-
         let two_pi_tick = self.params.two_pi_tick.load(Ordering::Relaxed);
 
-        let cutoff_norm = self.params.cutoff.unmodulated_normalized_value();
-        let cutoff_freq = MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(cutoff_norm);
+        // the curve follows host modulation, a ghost of it showing the unmodulated settings
+        let params = &self.params;
+        let (cutoff_norm, res) = params.morph(
+            params.cutoff.modulated_plain_value(),
+            params.res_value_at(params.res.modulated_normalized_value()),
+        );
+        let (ghost_cutoff_norm, ghost_res) = params.morphed_values();
+        let ghost_gain_db = params.effective_gain_db(params.gain.unmodulated_plain_value());
+        // as high as the filter reaches, like the knobs show
+        let filter_max_freq = params.max_freq.load(Ordering::Relaxed);
+        let cutoff_freq_hz = params.cutoff_range.cutoff_hz(cutoff_norm, filter_max_freq);
+        let ghost_cutoff_hz = params
+            .cutoff_range
+            .cutoff_hz(ghost_cutoff_norm, filter_max_freq);
+        let res = params.scaled_res(res, cutoff_freq_hz);
+        let ghost_res = params.scaled_res(ghost_res, ghost_cutoff_hz);
 
-        let max_freq =
-            f32::min(TAU / BASE_SAMPLE_RATE * MAX_FREQ, two_pi_tick * MAX_FREQ) / two_pi_tick;
+        let max_freq = filter_max_freq.min(axis_max);
 
         let mut freq = smoother.get_current()[0];
 
-        let cutoff_freq = f32::tan(cutoff_freq * two_pi_tick * 0.5);
+        // the filter runs at the oversampled rate, and is prewarped accordingly
+        let ratio = self.params.quality_settings().oversampling.ratio() as f32;
+        let os_tick = two_pi_tick / ratio;
+
+        let prewarp = self.params.prewarp.value();
+        let knob_res = res;
+        let (flavored_w_c, flavored_k) = self.params.flavor.value().apply(
+            f32x2::splat(cutoff_freq_hz * two_pi_tick),
+            f32x2::splat(res_to_damping(res)),
+        );
+        let cutoff_freq = prewarp.analog(flavored_w_c[0] / ratio);
+        let res = damping_to_res(flavored_k[0]);
 
-        let h = Filter::get_transfer_function::<f32>(self.params.mode.unmodulated_plain_value());
+        let mode = self.params.mode.modulated_plain_value();
+        let h64 = Filter::get_transfer_function::<f64>(mode.filter_mode());
+        let num_stages = self.params.slope.value().num_stages();
+        let response_at = |mode: Mode, freq: f32, analog_cutoff: f32, res: f32, gain: f32| {
+            transfer_function(
+                mode.filter_mode(),
+                num_stages,
+                freq * os_tick,
+                analog_cutoff,
+                res,
+                gain,
+            )
+        };
+        // the mode's own output scale, and the resonance's compensation
+        let output_scale = |mode: Mode, res: f32, gain: f32| {
+            mode.output_scale(gain) * self.params.res_compensation(mode, res, num_stages)
+        };
 
-        let res = self.params.res.unmodulated_plain_value();
-        let gain_normalized = self.params.gain.modulated_plain_value();
+        let gain_normalized = self
+            .params
+            .effective_gain_db(self.params.gain.modulated_plain_value());
         let gain = 10f32.powf(gain_normalized * (1. / 20.));
+        let trim = self.params.band1_input_gain.unmodulated_plain_value()
+            * self.params.band1_gain.unmodulated_plain_value();
 
-        let mut phase_color_buffer = self.phase_color_buffer.borrow_mut();
+        let num_phaser_stages = self.params.phaser_stages.value() as usize;
+        let phaser_feedback = self.params.phaser_feedback.unmodulated_plain_value();
+        let phaser_sweep = self.params.phaser_sweep.load(Ordering::Relaxed).exp2();
+        let phaser_cutoff =
+            f32::tan((cutoff_freq_hz * phaser_sweep).min(max_freq) * two_pi_tick * 0.5);
+        let phase_eq = self.params.phase_eq.unmodulated_plain_value();
+        let bank_bands = self.params.bank_bands.value() as usize;
+        let bank_spacing = self.params.bank_spacing.value();
 
-        let mut point_idx = 0;
-        while freq < max_freq {
-            let w = f32::tan(freq * two_pi_tick * 0.5) / cutoff_freq;
+        // per-channel coefficients, as seen by the audio thread, which deals in damping
+        let (w_c_lanes, res_lanes, gain_lanes) = self.params.coefficients.load();
+        let channel_cutoffs = (w_c_lanes * f32x2::splat(ratio.recip()))
+            .to_array()
+            .map(|w_c| prewarp.analog(w_c));
+        let channel_res = res_lanes.to_array().map(damping_to_res);
+        let channel_gains = gain_lanes.to_array();
+        let show_spread = channel_cutoffs[0] != channel_cutoffs[1]
+            || channel_res[0] != channel_res[1]
+            || channel_gains[0] != channel_gains[1];
 
-            let impedence = h(Complex::new(0., w), res, gain);
+        let mut points = self.points.borrow_mut();
+        points.reserve(num_points);
 
-            let gain_db = 10. * f32::log10(impedence.norm_sqr());
-            let offset = (gain_db / 35.) * bounds.height() / 2.;
+        // evaluate the response
 
-            phase_color_buffer.push(vg::Color::hsl(impedence.arg() / TAU, 1., 0.5));
+        let evaluate = |freq: f32, x: f32| {
+            let response = if high_res {
+                let w = f64::tan(freq as f64 * os_tick as f64 * 0.5) / cutoff_freq as f64;
+                let stage_gain = (gain as f64).powf(1. / num_stages as f64);
+                let response =
+                    h64(Complex::new(0., w), res as f64, stage_gain).powi(num_stages as i32);
+                Complex::new(response.re as f32, response.im as f32)
+            } else {
+                response_at(mode, freq, cutoff_freq, res, gain)
+            };
 
-            if point_idx == 0 {
-                plot.move_to(x, y - offset);
-                point_idx = 1;
+            let response = if bank_bands > 0 {
+                response
+                    + FilterBank::transfer_function(
+                        freq * os_tick,
+                        cutoff_freq_hz * os_tick,
+                        res,
+                        bank_spacing,
+                        bank_bands,
+                    )
             } else {
-                plot.line_to(x, y - offset);
+                response
+            };
+
+            let mut impedence = response
+                * trim
+                * output_scale(mode, res, gain)
+                * self.params.band2_response(freq * two_pi_tick);
+
+            if phase_eq > 0. {
+                impedence *= PhaseEq::transfer_function(
+                    freq * two_pi_tick,
+                    cutoff_freq_hz * two_pi_tick,
+                    res,
+                    phase_eq,
+                );
+            }
+
+            if num_phaser_stages > 0 {
+                let w_phaser = f32::tan(freq * two_pi_tick * 0.5) / phaser_cutoff;
+                let delay = Complex::from_polar(1., -freq * two_pi_tick);
+                impedence *= Phaser::transfer_function(
+                    w_phaser,
+                    delay,
+                    res,
+                    num_phaser_stages,
+                    phaser_feedback,
+                );
+            }
+
+            let spread = show_spread.then(|| {
+                let [db_l, db_r] = [0, 1].map(|i| {
+                    let gain = channel_gains[i];
+                    let h = response_at(mode, freq, channel_cutoffs[i], channel_res[i], gain)
+                        * trim
+                        * output_scale(mode, channel_res[i], gain);
+                    10. * f32::log10(h.norm_sqr())
+                });
+                (db_l, db_r)
+            });
+
+            PlotPoint {
+                x,
+                freq,
+                gain_db: 10. * f32::log10(impedence.norm_sqr()),
+                phase: impedence.arg(),
+                spread,
             }
+        };
+
+        // steeper steps than this, in dB, between neighbouring points are subdivided
+        const STEEP_DB: f32 = 1.5;
+        const MAX_SUBDIVISIONS: f32 = 16.;
+        let adaptive = self.params.display.adaptive_density.value();
+
+        while freq < max_freq {
+            let point = evaluate(freq, x);
+
+            if let Some(prev) = points.last().filter(|_| adaptive) {
+                let (prev_freq, prev_x) = (prev.freq, prev.x);
+                let step_db = (point.gain_db - prev.gain_db).abs();
+
+                if step_db > STEEP_DB {
+                    let n = (step_db / STEEP_DB).ceil().min(MAX_SUBDIVISIONS) as usize;
+                    for k in 1..n {
+                        let t = k as f32 / n as f32;
+                        let sub_freq = prev_freq * (freq / prev_freq).powf(t);
+                        points.push(evaluate(sub_freq, prev_x + (x - prev_x) * t));
+                    }
+                }
+            }
+
+            points.push(point);
 
             x += delta_x;
 
@@ -101,17 +1155,321 @@ impl View for SVFBode {
             freq = smoother.get_current()[0];
         }
 
-        let actual_num_pts = phase_color_buffer.len();
+        // pick the vertical range, either fixed or fit to the curve
+
+        let (center_db, half_range_db) = if self.params.display.auto_y.value() {
+            const MARGIN_DB: f32 = 3.;
+            const MIN_HALF_RANGE_DB: f32 = 6.;
+
+            let (min_db, max_db) = points
+                .iter()
+                .map(|point| point.gain_db)
+                .filter(|db| db.is_finite())
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), db| {
+                    (min.min(db), max.max(db))
+                });
+
+            if min_db <= max_db {
+                (
+                    (min_db + max_db) / 2.,
+                    ((max_db - min_db) / 2. + MARGIN_DB).max(MIN_HALF_RANGE_DB),
+                )
+            } else {
+                (0., DEFAULT_HALF_RANGE_DB)
+            }
+        } else {
+            (0., DEFAULT_HALF_RANGE_DB)
+        };
+
+        let db_to_y = |db: f32| y - (db - center_db) / half_range_db * bounds.height() / 2.;
+
+        {
+            let mut snapshot = self.params.editor.plot_snapshot.lock().unwrap();
+            snapshot.curve.clear();
+            snapshot
+                .curve
+                .extend(points.iter().map(|point| (point.freq, point.gain_db)));
+            snapshot.freq_axis = (axis_min, axis_max);
+            snapshot.db_range = (center_db, half_range_db);
+        }
+        let baseline = db_to_y(0.);
+
+        self.draw_grid(
+            canvas,
+            bounds,
+            &freq_to_x,
+            &db_to_y,
+            (center_db, half_range_db),
+        );
+
+        if self.params.display.input_spectrum.value() {
+            self.draw_input_spectrum(canvas, bounds, &db_to_y);
+        }
+
+        // build the paths
+
+        let mut plot = vg::Path::new();
+        let mut fill = vg::Path::new();
+        let mut inverse = vg::Path::new();
+        let mut phase = vg::Path::new();
+        let mut last_phase = 0.;
+
+        if let Some(first) = points.first() {
+            fill.move_to(first.x, baseline);
+        }
+
+        for (i, point) in points.iter().enumerate() {
+            let curve_y = db_to_y(point.gain_db);
+            // 1 / |H| is just the curve mirrored around 0 dB
+            let inverse_y = db_to_y(-point.gain_db);
+
+            if i == 0 {
+                plot.move_to(point.x, curve_y);
+                inverse.move_to(point.x, inverse_y);
+            } else {
+                plot.line_to(point.x, curve_y);
+                inverse.line_to(point.x, inverse_y);
+            }
+
+            fill.line_to(point.x, curve_y);
+
+            // ±180° spans the full height, don't connect points across phase wraps
+            let phase_y = y - point.phase / PI * bounds.height() / 2.;
+            if i > 0 && (point.phase - last_phase).abs() < PI {
+                phase.line_to(point.x, phase_y);
+            } else {
+                phase.move_to(point.x, phase_y);
+            }
+            last_phase = point.phase;
+        }
+
+        // close the fill down to the 0 dB baseline
+
+        if let Some(last) = points.last() {
+            fill.line_to(last.x, baseline);
+        }
+        fill.close();
+
+        if self.params.display.fill_curve.value() {
+            canvas.fill_path(&fill, &vg::Paint::color(vg::Color::rgba(255, 255, 255, 48)));
+        }
+
+        if self.params.display.inverse_curve.value() {
+            canvas.stroke_path(
+                &inverse,
+                &vg::Paint::color(vg::Color::rgba(255, 255, 255, 96))
+                    .with_line_width(1.5)
+                    .with_anti_alias(true),
+            );
+        }
+
+        if self.params.display.phase_curve.value() {
+            let mut guides = vg::Path::new();
+            for angle in [-PI, -FRAC_PI_2, 0., FRAC_PI_2, PI] {
+                let guide_y = y - angle / PI * bounds.height() / 2.;
+                dashed_hline(&mut guides, bounds.x, bounds.x + width, guide_y, 4.);
+            }
+
+            canvas.stroke_path(
+                &guides,
+                &vg::Paint::color(vg::Color::rgba(255, 255, 255, 64)).with_line_width(1.),
+            );
+
+            canvas.stroke_path(
+                &phase,
+                &vg::Paint::color(vg::Color::rgba(120, 200, 255, 160))
+                    .with_line_width(1.5)
+                    .with_anti_alias(true),
+            );
+        }
+
+        // shade the region between the left and right channels' responses
+
+        let spread_points = || {
+            points
+                .iter()
+                .filter_map(|point| point.spread.map(|(l, r)| (point.x, l, r)))
+        };
+
+        if let Some((first_x, first_db, _)) = spread_points().next() {
+            let mut spread = vg::Path::new();
+            spread.move_to(first_x, db_to_y(first_db));
+            for (x, db_l, _) in spread_points() {
+                spread.line_to(x, db_to_y(db_l));
+            }
+            for (x, _, db_r) in spread_points().collect::<Vec<_>>().into_iter().rev() {
+                spread.line_to(x, db_to_y(db_r));
+            }
+            spread.close();
+
+            canvas.fill_path(
+                &spread,
+                &vg::Paint::color(vg::Color::rgba(255, 200, 80, 64)),
+            );
+        }
+
+        if (ghost_cutoff_norm, ghost_res, ghost_gain_db) != (cutoff_norm, knob_res, gain_normalized)
+        {
+            let ghost_cutoff = prewarp.analog(ghost_cutoff_hz * os_tick);
+            let ghost_gain = 10f32.powf(ghost_gain_db * (1. / 20.));
+
+            let mut ghost = vg::Path::new();
+
+            for (i, point) in points.iter().enumerate() {
+                let response = response_at(mode, point.freq, ghost_cutoff, ghost_res, ghost_gain)
+                    * trim
+                    * output_scale(mode, ghost_res, ghost_gain);
+                let ghost_y = db_to_y(10. * f32::log10(response.norm_sqr()));
+
+                if i == 0 {
+                    ghost.move_to(point.x, ghost_y);
+                } else {
+                    ghost.line_to(point.x, ghost_y);
+                }
+            }
+
+            canvas.stroke_path(
+                &ghost,
+                &vg::Paint::color(vg::Color::rgba(255, 255, 255, 80))
+                    .with_line_width(1.5)
+                    .with_anti_alias(true),
+            );
+        }
+
+        // mark the cutoff the filter actually runs at, redder the more resonant it is
+
+        let marker_freq = 2. * f32::atan(cutoff_freq) / os_tick;
+        if (axis_min..max_freq).contains(&marker_freq) {
+            let resonance = ((MAX_RES - res) / (MAX_RES - MIN_RES)).clamp(0., 1.);
+            let color = vg::Color::rgba(
+                (120. + 135. * resonance) as u8,
+                (160. - 120. * resonance) as u8,
+                (255. - 215. * resonance) as u8,
+                110,
+            );
+
+            let cutoff_x = freq_to_x(marker_freq);
+            let mut marker = vg::Path::new();
+            marker.move_to(cutoff_x, bounds.y);
+            marker.line_to(cutoff_x, bounds.y + bounds.h);
+            canvas.stroke_path(&marker, &vg::Paint::color(color).with_line_width(1.));
+        }
+
+        // follow the envelope and key tracking's modulation of the cutoff, and the extremes
+        // it reached lately, see `EditorParams::follow_modulation`
+
+        let octaves = self.params.cutoff_mod.load(Ordering::Relaxed);
+        let (mod_min, mod_max) = *self.params.editor.mod_range.lock().unwrap();
+
+        if octaves != 0. || mod_min != mod_max {
+            let mod_freq = cutoff_freq_hz * octaves.exp2();
+            if (axis_min..max_freq).contains(&mod_freq) {
+                let mod_x = freq_to_x(mod_freq);
+                let mut marker = vg::Path::new();
+                marker.move_to(mod_x, bounds.y);
+                marker.line_to(mod_x, bounds.y + bounds.h);
+                canvas.stroke_path(
+                    &marker,
+                    &vg::Paint::color(vg::Color::rgba(255, 255, 120, 140)).with_line_width(1.),
+                );
+            }
+
+            if self.params.display.mod_extremes.value() {
+                for extreme in [mod_min, mod_max] {
+                    let extreme_cutoff = prewarp.analog(
+                        (cutoff_freq_hz * extreme.exp2())
+                            .clamp(params.cutoff_range.min_hz, max_freq)
+                            * os_tick,
+                    );
+                    let mut curve = vg::Path::new();
+
+                    for (i, point) in points.iter().enumerate() {
+                        let response = response_at(mode, point.freq, extreme_cutoff, res, gain)
+                            * trim
+                            * output_scale(mode, res, gain);
+                        let curve_y = db_to_y(10. * f32::log10(response.norm_sqr()));
+
+                        if i == 0 {
+                            curve.move_to(point.x, curve_y);
+                        } else {
+                            curve.line_to(point.x, curve_y);
+                        }
+                    }
+
+                    canvas.stroke_path(
+                        &curve,
+                        &vg::Paint::color(vg::Color::rgba(255, 255, 120, 48))
+                            .with_line_width(1.)
+                            .with_anti_alias(true),
+                    );
+                }
+            }
+        }
+
+        // the comparison mode, at the same cutoff, resonance and gain
+
+        let compare_mode = *self.params.editor.compare_mode.lock().unwrap();
+        if let Some(compare_mode) = compare_mode.filter(|&compare_mode| compare_mode != mode) {
+            let mut compare = vg::Path::new();
+
+            for (i, point) in points.iter().enumerate() {
+                let response = response_at(compare_mode, point.freq, cutoff_freq, res, gain)
+                    * trim
+                    * output_scale(compare_mode, res, gain);
+                let compare_y = db_to_y(10. * f32::log10(response.norm_sqr()));
+
+                if i == 0 {
+                    compare.move_to(point.x, compare_y);
+                } else {
+                    compare.line_to(point.x, compare_y);
+                }
+            }
+
+            canvas.stroke_path(
+                &compare,
+                &vg::Paint::color(vg::Color::rgba(255, 160, 220, 96))
+                    .with_line_width(1.5)
+                    .with_anti_alias(true),
+            );
+
+            let _ = canvas.fill_text(
+                bounds.x + 6.,
+                bounds.y + bounds.h - 6. - self.line_height(),
+                format!("vs. {}", Mode::variants()[compare_mode.to_index()]),
+                &self.label_paint(vg::Color::rgba(255, 160, 220, 180)),
+            );
+        }
+
+        let display = &self.params.display;
+        let (saturation, lightness) = (
+            display.phase_saturation.value(),
+            display.phase_lightness.value(),
+        );
+        let colorblind = display.phase_colorblind.value();
+        let phase_color = |phase: f32| {
+            if colorblind {
+                // blue at -180°, through grey, to orange at +180°
+                const BLUE: [f32; 3] = [0., 0.45, 0.7];
+                const GREY: [f32; 3] = [0.6, 0.6, 0.6];
+                const ORANGE: [f32; 3] = [0.9, 0.62, 0.];
+
+                let t = (phase / PI).clamp(-1., 1.);
+                let (from, to) = if t < 0. { (GREY, BLUE) } else { (GREY, ORANGE) };
+                let [r, g, b] = core::array::from_fn(|i| from[i] + (to[i] - from[i]) * t.abs());
+                vg::Color::rgbf(r, g, b)
+            } else {
+                vg::Color::hsl(phase / TAU, saturation, lightness)
+            }
+        };
 
         let paint = vg::Paint::linear_gradient_stops(
             0.,
             0.,
             width,
             0.,
-            phase_color_buffer
+            points
                 .iter()
-                .enumerate()
-                .map(|(i, &c)| (i as f32 / actual_num_pts as f32, c)),
+                .map(|point| ((point.x - bounds.x) / width, phase_color(point.phase))),
         )
         .with_miter_limit(0.)
         .with_line_width(3.)
@@ -120,6 +1478,280 @@ impl View for SVFBode {
 
         canvas.stroke_path(&plot, &paint);
 
-        phase_color_buffer.clear();
+        points.clear();
+
+        // mark the resonance peak, as high as the whole cascade and its scaling put it
+
+        if let Some(w_peak) = resonance_peak(mode, res) {
+            let peak_freq = 2. * f32::atan(w_peak * cutoff_freq) / os_tick;
+
+            if (axis_min..max_freq).contains(&peak_freq) {
+                let peak = response_at(mode, peak_freq, cutoff_freq, res, gain)
+                    * trim
+                    * output_scale(mode, res, gain);
+                let peak_db = 10. * f32::log10(peak.norm_sqr());
+                let peak_x = freq_to_x(peak_freq);
+                let peak_y = db_to_y(peak_db);
+
+                let mut marker = vg::Path::new();
+                marker.circle(peak_x, peak_y, 3.);
+                canvas.fill_path(&marker, &vg::Paint::color(vg::Color::white()));
+
+                let _ = canvas.fill_text(
+                    peak_x + 5.,
+                    peak_y - 5.,
+                    format!("{peak_db:+.1} dB"),
+                    &self.label_paint(vg::Color::white()),
+                );
+            }
+        }
+
+        // draw the target curve
+
+        let target_curve = self.params.editor.target_curve.lock().unwrap();
+        let mut target = vg::Path::new();
+
+        for (i, &(freq, gain_db)) in target_curve.iter().enumerate() {
+            let target_x = freq_to_x(freq);
+            let target_y = db_to_y(gain_db);
+
+            if i == 0 {
+                target.move_to(target_x, target_y);
+            } else {
+                target.line_to(target_x, target_y);
+            }
+        }
+
+        canvas.stroke_path(
+            &target,
+            &vg::Paint::color(vg::Color::rgba(80, 255, 120, 160))
+                .with_line_width(1.5)
+                .with_anti_alias(true),
+        );
+
+        // dim everything outside the focus window
+
+        if let Some((low, high)) = self.params.editor.focus() {
+            let (low_x, high_x) = (freq_to_x(low), freq_to_x(high));
+
+            let mut dimmed = vg::Path::new();
+            dimmed.rect(bounds.x, bounds.y, low_x - bounds.x, bounds.h);
+            dimmed.rect(high_x, bounds.y, bounds.x + bounds.w - high_x, bounds.h);
+            canvas.fill_path(&dimmed, &vg::Paint::color(vg::Color::rgba(0, 0, 0, 160)));
+        }
+
+        // read the resonance out as a Q and the matching -3 dB bandwidth, in octaves,
+        // from the denominator `s² + 2·res·s + 1`
+
+        let q = res_to_damping(res).recip();
+        let bandwidth = 2. / LN_2 * res.asinh();
+        let _ = canvas.fill_text(
+            bounds.x + 6.,
+            bounds.y + bounds.h - 6.,
+            format!("Q {q:.2}  BW {bandwidth:.2} oct"),
+            &self.label_paint(vg::Color::rgba(255, 255, 255, 180)),
+        );
+
+        // warn about what a mono fold-down would lose
+
+        let input_correlation = self.params.input_correlation.load(Ordering::Relaxed);
+        let output_correlation = self.params.output_correlation.load(Ordering::Relaxed);
+        let stereo_effects = self.params.width.value() != 1. && !self.params.dual_mono.value();
+
+        const MONO_CORRELATION: f32 = 0.999;
+        const CANCELLING_CORRELATION: f32 = -0.5;
+        let mono_warning = if output_correlation < CANCELLING_CORRELATION {
+            Some("output cancels when summed to mono")
+        } else if stereo_effects && input_correlation > MONO_CORRELATION {
+            Some("mono input, stereo effects lost on fold-down")
+        } else {
+            None
+        };
+
+        if let Some(warning) = mono_warning {
+            let _ = canvas.fill_text(
+                bounds.x + bounds.w - 24.,
+                bounds.y + 6. + 2. * self.line_height(),
+                warning,
+                &self
+                    .label_paint(vg::Color::rgba(255, 200, 80, 180))
+                    .with_text_align(vg::Align::Right),
+            );
+        }
+
+        // the (left lane's) coefficients, as the SVF's `g` and `R`, and as a biquad
+
+        if self.params.display.show_coefficients.value() {
+            let (w_c, k, _) = self.params.coefficients.load();
+            let (w_c, k) = self.params.flavor.value().apply(w_c, k);
+            let g = prewarp.analog(w_c[0]);
+            let [b0, b1, b2, a1, a2] = self.params.biquad_coeffs()[0];
+
+            let lines = [
+                format!("g {g:.5}  R {:.5}", damping_to_res(k[0])),
+                format!("b {b0:.5}  {b1:.5}  {b2:.5}"),
+                format!("a 1  {a1:.5}  {a2:.5}"),
+            ];
+            for (i, line) in lines.into_iter().enumerate() {
+                let _ = canvas.fill_text(
+                    bounds.x + 6.,
+                    bounds.y + (3. + i as f32) * self.line_height(),
+                    line,
+                    &self.label_paint(vg::Color::rgba(255, 255, 255, 160)),
+                );
+            }
+        }
+
+        // read out the host's settings
+
+        let block_size = self.params.block_size.load(Ordering::Relaxed);
+        let block_size = if block_size > 0 {
+            format!(", {block_size} samples")
+        } else {
+            String::new()
+        };
+        let _ = canvas.fill_text(
+            bounds.x + bounds.w - 24.,
+            bounds.y + 6. + self.line_height(),
+            format!("{:.0} Hz{block_size}", TAU / two_pi_tick),
+            &self
+                .label_paint(vg::Color::rgba(255, 255, 255, 140))
+                .with_text_align(vg::Align::Right),
+        );
+
+        // draw the current mode's icon
+
+        const ICON_SIZE: (f32, f32) = (28., 16.);
+        draw_mode_icon(
+            canvas,
+            mode,
+            bounds.x + bounds.w - ICON_SIZE.0 - 6.,
+            bounds.y + bounds.h - ICON_SIZE.1 - 6.,
+            ICON_SIZE.0,
+            ICON_SIZE.1,
+        );
+
+        // draw self-oscillation warning
+
+        if res < SELF_OSC_RES {
+            let intensity = ((SELF_OSC_RES - res) / (SELF_OSC_RES - MIN_RES)).clamp(0., 1.);
+            let alpha = (100. + 155. * intensity) as u8;
+
+            const SIZE: f32 = 12.;
+            let (right, top) = (bounds.x + bounds.w - 6., bounds.y + 6.);
+
+            let mut warning = vg::Path::new();
+            warning.move_to(right - SIZE / 2., top);
+            warning.line_to(right, top + SIZE);
+            warning.line_to(right - SIZE, top + SIZE);
+            warning.close();
+
+            canvas.fill_path(
+                &warning,
+                &vg::Paint::color(vg::Color::rgba(255, 40, 40, alpha)),
+            );
+        }
+
+        // read the response out under the mouse
+
+        if let Some(hover_x) = self
+            .hover_x
+            .filter(|&x| x >= bounds.x && x <= bounds.x + width)
+        {
+            let point = evaluate(self.x_to_freq(bounds, hover_x), hover_x);
+
+            let mut marker = vg::Path::new();
+            marker.move_to(hover_x, bounds.y);
+            marker.line_to(hover_x, bounds.y + bounds.h);
+            canvas.stroke_path(
+                &marker,
+                &vg::Paint::color(vg::Color::rgba(255, 255, 255, 96)).with_line_width(1.),
+            );
+
+            let freq = if point.freq >= 1000. {
+                format!("{:.2} kHz", point.freq / 1000.)
+            } else {
+                format!("{:.1} Hz", point.freq)
+            };
+
+            // flipped to the cursor's left past the middle, to stay inside the plot
+            let (text_x, align) = if hover_x < bounds.x + width / 2. {
+                (hover_x + 4., vg::Align::Left)
+            } else {
+                (hover_x - 4., vg::Align::Right)
+            };
+            let _ = canvas.fill_text(
+                text_x,
+                bounds.y + 6. + 3. * self.line_height(),
+                format!(
+                    "{freq} / {:.1} dB / {:.0}°",
+                    point.gain_db,
+                    point.phase.to_degrees()
+                ),
+                &self
+                    .label_paint(vg::Color::rgba(255, 255, 255, 200))
+                    .with_text_align(align),
+            );
+        }
+
+        canvas.restore();
+    }
+
+    /// Draws the spectrum of the latest output samples, on the same frequency axis as the response.
+    fn draw_spectrum(&self, canvas: &mut Canvas, bounds: BoundingBox) {
+        let mut bg = vg::Path::new();
+        bg.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+        canvas.fill_path(&bg, &vg::Paint::color(vg::Color::rgb(16, 16, 24)));
+
+        let spectra = self.params.editor.spectra.lock().unwrap();
+        let spectrum = &spectra.output;
+
+        let mut path = vg::Path::new();
+        let bottom = bounds.y + bounds.h;
+        path.move_to(bounds.x, bottom);
+
+        for (bin, &db) in spectrum.levels().iter().enumerate() {
+            let x_norm = bin as f32 / (SPECTRUM_NUM_BINS - 1) as f32;
+            let y = bottom - (1. - db / SPECTRUM_FLOOR_DB) * bounds.h;
+
+            path.line_to(bounds.x + x_norm * bounds.w, y);
+        }
+
+        path.line_to(bounds.x + bounds.w, bottom);
+        path.close();
+
+        canvas.fill_path(&path, &vg::Paint::color(vg::Color::rgba(80, 160, 255, 96)));
+    }
+
+    /// The input's spectrum, faintly, on the response's own axes. Not drawn until some
+    /// audio has come through.
+    fn draw_input_spectrum(
+        &self,
+        canvas: &mut Canvas,
+        bounds: BoundingBox,
+        db_to_y: &dyn Fn(f32) -> f32,
+    ) {
+        let spectra = self.params.editor.spectra.lock().unwrap();
+        let levels = spectra.input.levels();
+        let num_bins = levels.len();
+        if levels.iter().all(|&db| db <= SPECTRUM_FLOOR_DB) {
+            return;
+        }
+
+        let mut path = vg::Path::new();
+        let bottom = bounds.y + bounds.h;
+        path.move_to(bounds.x, bottom);
+
+        for (bin, &db) in levels.iter().enumerate() {
+            let x_norm = bin as f32 / (SPECTRUM_NUM_BINS - 1) as f32;
+            let y = db_to_y(db).clamp(bounds.y, bottom);
+            path.line_to(bounds.x + x_norm * bounds.w, y);
+        }
+
+        let x_end = bounds.x + (num_bins - 1) as f32 / (SPECTRUM_NUM_BINS - 1) as f32 * bounds.w;
+        path.line_to(x_end, bottom);
+        path.close();
+
+        canvas.fill_path(&path, &vg::Paint::color(vg::Color::rgba(255, 255, 255, 24)));
     }
 }
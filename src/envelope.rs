@@ -0,0 +1,72 @@
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    #[default]
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Linear ADSR, advanced once per processed block. Times are in seconds,
+/// and every segment travels at a rate of one full-scale unit per segment time.
+#[derive(Default)]
+pub struct Adsr {
+    stage: Stage,
+    level: f32,
+    held_notes: usize,
+}
+
+impl Adsr {
+    pub fn note_on(&mut self) {
+        self.held_notes += 1;
+        self.stage = Stage::Attack;
+    }
+
+    pub fn note_off(&mut self) {
+        self.held_notes = self.held_notes.saturating_sub(1);
+        if self.held_notes == 0 && self.stage != Stage::Idle {
+            self.stage = Stage::Release;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn advance(
+        &mut self,
+        mut elapsed: f32,
+        attack: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+    ) -> f32 {
+        while elapsed > 0. {
+            let (target, time, next) = match self.stage {
+                Stage::Idle | Stage::Sustain => break,
+                Stage::Attack => (1., attack, Stage::Decay),
+                Stage::Decay => (sustain, decay, Stage::Sustain),
+                Stage::Release => (0., release, Stage::Idle),
+            };
+
+            let distance = (target - self.level).abs();
+            let remaining = distance * time;
+
+            if elapsed < remaining {
+                self.level += (target - self.level).signum() * elapsed / time;
+                break;
+            }
+
+            self.level = target;
+            self.stage = next;
+            elapsed -= remaining;
+        }
+
+        if self.stage == Stage::Sustain {
+            self.level = sustain;
+        }
+
+        self.level
+    }
+}
@@ -0,0 +1,86 @@
+use core::fmt::Write;
+use std::{fs, io, path::PathBuf};
+
+const WIDTH: f32 = 800.;
+const HEIGHT: f32 = 400.;
+
+/// The response as last drawn by the editor, in display units.
+#[derive(Default, Clone)]
+pub struct PlotSnapshot {
+    /// `(frequency (Hz), gain (dB))` points
+    pub curve: Vec<(f32, f32)>,
+    /// lowest and highest frequencies of the x-axis
+    pub freq_axis: (f32, f32),
+    /// center and half the height of the y-axis, in dB
+    pub db_range: (f32, f32),
+}
+
+impl PlotSnapshot {
+    fn x(&self, freq: f32) -> f32 {
+        let (min, max) = self.freq_axis;
+        WIDTH * (freq / min).ln() / (max / min).ln()
+    }
+
+    fn y(&self, db: f32) -> f32 {
+        let (center, half_range) = self.db_range;
+        HEIGHT * 0.5 * (1. - (db - center) / half_range)
+    }
+
+    /// An SVG image of the curve, over decade and 6 dB gridlines.
+    pub fn to_svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"black\"/>\n"
+        );
+
+        let grid = |svg: &mut String, (x1, y1, x2, y2): (f32, f32, f32, f32), opacity: f32| {
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" \
+                 stroke=\"white\" stroke-opacity=\"{opacity}\"/>"
+            );
+        };
+
+        let (min_freq, max_freq) = self.freq_axis;
+        let mut decade = 10f32.powf(min_freq.log10().ceil());
+        while decade <= max_freq {
+            let x = self.x(decade);
+            grid(&mut svg, (x, 0., x, HEIGHT), 0.25);
+            decade *= 10.;
+        }
+
+        let (center, half_range) = self.db_range;
+        let mut db = 6. * ((center - half_range) / 6.).ceil();
+        while db <= center + half_range {
+            let y = self.y(db);
+            grid(
+                &mut svg,
+                (0., y, WIDTH, y),
+                if db == 0. { 0.5 } else { 0.25 },
+            );
+            db += 6.;
+        }
+
+        let points = self
+            .curve
+            .iter()
+            .filter(|(_, db)| db.is_finite())
+            .map(|&(freq, db)| format!("{:.1},{:.1}", self.x(freq), self.y(db)))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{points}\" fill=\"none\" stroke=\"white\" stroke-width=\"2\"/>"
+        );
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Writes `snapshot` to an SVG file in the temporary directory, returning its path.
+pub fn write_svg(snapshot: &PlotSnapshot) -> io::Result<PathBuf> {
+    let path = std::env::temp_dir().join("svf-response.svg");
+    fs::write(&path, snapshot.to_svg())?;
+    Ok(path)
+}
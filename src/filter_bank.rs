@@ -0,0 +1,96 @@
+use nih_plug::prelude::Enum;
+#[cfg(feature = "gui")]
+use num::Complex;
+use plugin_util::{filter::svf::FilterMode, simd::*};
+
+use crate::{Filter, TAU};
+
+pub const MAX_BANDS: usize = 8;
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BankSpacing {
+    /// at every multiple of the cutoff
+    #[default]
+    #[name = "Harmonic"]
+    Harmonic,
+    /// an octave apart from each other
+    #[name = "Octaves"]
+    Octaves,
+}
+
+impl BankSpacing {
+    /// The ratio of the `band`th (from 0) extra band's cutoff to the main one.
+    fn ratio(self, band: usize) -> f32 {
+        match self {
+            Self::Harmonic => (band + 2) as f32,
+            Self::Octaves => ((band + 1) as f32).exp2(),
+        }
+    }
+}
+
+/// Unit gain bandpasses above the cutoff, run in parallel with the main filter, and
+/// summed with its output, for formant-like textures.
+#[derive(Default)]
+pub struct FilterBank {
+    bands: [Filter; MAX_BANDS],
+}
+
+impl FilterBank {
+    pub fn reset(&mut self) {
+        self.bands.iter_mut().for_each(Filter::reset);
+    }
+
+    fn band_cutoff(w_c: f32x2, spacing: BankSpacing, band: usize) -> f32x2 {
+        (w_c * Simd::splat(spacing.ratio(band))).simd_min(Simd::splat(0.95 * TAU / 2.))
+    }
+
+    pub fn update(&mut self, w_c: f32x2, res: f32x2, spacing: BankSpacing, inc: f32x2) {
+        let update = Filter::get_smoothing_update_function(FilterMode::BP1);
+        for (band, filter) in self.bands.iter_mut().enumerate() {
+            let w = Self::band_cutoff(w_c, spacing, band);
+            update(filter, w, res, Simd::splat(1.), inc);
+        }
+    }
+
+    pub fn prime(&mut self, w_c: f32x2, res: f32x2, spacing: BankSpacing) {
+        let update = Filter::get_update_function(FilterMode::BP1);
+        for (band, filter) in self.bands.iter_mut().enumerate() {
+            let w = Self::band_cutoff(w_c, spacing, band);
+            update(filter, w, res, Simd::splat(1.));
+        }
+    }
+
+    /// The sum of the first `num_bands` bands' outputs for `sample`.
+    pub fn process(&mut self, sample: f32x2, num_bands: usize) -> f32x2 {
+        let get_output = Filter::get_output_function(FilterMode::BP1);
+
+        self.bands[..num_bands]
+            .iter_mut()
+            .fold(Simd::splat(0.), |sum, band| {
+                band.update_all_smoothers();
+                band.process(sample);
+                sum + get_output(band)
+            })
+    }
+
+    /// Response of the first `num_bands` bands, summed, at the frequency `w`, the main
+    /// cutoff being `w_c`, both in radians per sample. `res` is the editor-side resonance.
+    #[cfg(feature = "gui")]
+    pub fn transfer_function(
+        w: f32,
+        w_c: f32,
+        res: f32,
+        spacing: BankSpacing,
+        num_bands: usize,
+    ) -> Complex<f32> {
+        let h = Filter::get_transfer_function::<f32>(FilterMode::BP1);
+
+        (0..num_bands)
+            .map(|band| {
+                let cutoff = Self::band_cutoff(Simd::splat(w_c), spacing, band)[0];
+                let s = Complex::new(0., f32::tan(w * 0.5) / f32::tan(cutoff * 0.5));
+                h(s, res, 1.)
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,27 @@
+/// Peak envelope follower, with separate attack and release times.
+#[derive(Default)]
+pub struct Follower {
+    level: f32,
+}
+
+impl Follower {
+    pub fn reset(&mut self) {
+        self.level = 0.;
+    }
+
+    /// Coefficient of the one-pole smoothing for a time constant of `time_s` seconds.
+    pub fn coefficient(time_s: f32, two_pi_tick: f32) -> f32 {
+        f32::exp(-two_pi_tick / (core::f32::consts::TAU * time_s))
+    }
+
+    pub fn process(&mut self, sample: f32, attack: f32, release: f32) -> f32 {
+        let input = sample.abs();
+        let coeff = if input > self.level { attack } else { release };
+        self.level = input + (self.level - input) * coeff;
+        self.level
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+}
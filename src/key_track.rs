@@ -0,0 +1,41 @@
+/// Follows the last played note, gliding linearly between notes over a
+/// configurable time. Retargeting mid-glide continues from the current position.
+#[derive(Default)]
+pub struct KeyTracker {
+    current: Option<f32>,
+    target: f32,
+    step: f32,
+}
+
+impl KeyTracker {
+    pub fn note_on(&mut self, note: u8, glide_samples: f32) {
+        self.target = note as f32;
+
+        match self.current {
+            Some(current) if glide_samples >= 1. => {
+                self.step = (self.target - current).abs() / glide_samples;
+            }
+            _ => self.current = Some(self.target),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the (fractional) tracked note, `None` if no note was played yet.
+    pub fn advance(&mut self, num_samples: usize) -> Option<f32> {
+        let current = self.current.as_mut()?;
+
+        let distance = self.target - *current;
+        let max_step = self.step * num_samples as f32;
+
+        *current = if distance.abs() <= max_step {
+            self.target
+        } else {
+            *current + max_step.copysign(distance)
+        };
+
+        Some(*current)
+    }
+}
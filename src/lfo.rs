@@ -0,0 +1,160 @@
+use nih_plug::prelude::Enum;
+
+use crate::TAU;
+
+/// Tempo used while the host doesn't report one
+const DEFAULT_TEMPO: f64 = 120.;
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LfoRate {
+    #[name = "1/1"]
+    Whole,
+    #[name = "1/2"]
+    Half,
+    #[default]
+    #[name = "1/4"]
+    Quarter,
+    #[name = "1/8"]
+    Eighth,
+    #[name = "1/16"]
+    Sixteenth,
+    #[name = "1/4T"]
+    QuarterTriplet,
+    #[name = "1/8T"]
+    EighthTriplet,
+    #[name = "1/16T"]
+    SixteenthTriplet,
+}
+
+impl LfoRate {
+    /// Length of a cycle, in quarter notes.
+    fn beats(self) -> f64 {
+        match self {
+            Self::Whole => 4.,
+            Self::Half => 2.,
+            Self::Quarter => 1.,
+            Self::Eighth => 0.5,
+            Self::Sixteenth => 0.25,
+            Self::QuarterTriplet => 2. / 3.,
+            Self::EighthTriplet => 1. / 3.,
+            Self::SixteenthTriplet => 1. / 6.,
+        }
+    }
+}
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LfoShape {
+    #[default]
+    #[name = "Sine"]
+    Sine,
+    #[name = "Triangle"]
+    Triangle,
+    /// falling, as wobbles usually are
+    #[name = "Saw"]
+    Saw,
+}
+
+impl LfoShape {
+    /// The shape's value, from -1 to 1, at `phase`, from 0 to 1.
+    fn value(self, phase: f32) -> f32 {
+        match self {
+            Self::Sine => f32::sin(phase * TAU),
+            Self::Triangle => 1. - 4. * (phase - 0.5).abs(),
+            Self::Saw => 1. - 2. * phase,
+        }
+    }
+}
+
+/// How far, in quarter notes, the playhead moves per sample.
+pub fn beats_per_sample(tempo: Option<f64>, sample_rate: f32) -> f64 {
+    tempo.unwrap_or(DEFAULT_TEMPO) / 60. / f64::from(sample_rate)
+}
+
+/// A tempo-synced LFO, locked to the host's timeline while it plays, free-running otherwise.
+#[derive(Default)]
+pub struct Lfo {
+    phase: f64,
+}
+
+impl Lfo {
+    pub fn reset(&mut self) {
+        self.phase = 0.;
+    }
+
+    /// Advances the LFO by `num_samples`, returning its value at the start of the block.
+    /// `pos_beats` is the host's playhead position, in quarter notes, if it's playing.
+    pub fn advance(
+        &mut self,
+        rate: LfoRate,
+        shape: LfoShape,
+        tempo: Option<f64>,
+        pos_beats: Option<f64>,
+        num_samples: usize,
+        sample_rate: f32,
+    ) -> f32 {
+        let beats = rate.beats();
+
+        if let Some(pos_beats) = pos_beats {
+            self.phase = (pos_beats / beats).rem_euclid(1.);
+        }
+        let value = shape.value(self.phase as f32);
+
+        let beats_per_sample = beats_per_sample(tempo, sample_rate);
+        self.phase = (self.phase + num_samples as f64 * beats_per_sample / beats).fract();
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.;
+    const BLOCK_LEN: usize = 32;
+
+    /// The LFO's values, a block at a time, locked to a playhead starting at `start_beats`
+    /// or free-running.
+    fn run(rate: LfoRate, start_beats: Option<f64>, num_blocks: usize) -> Vec<f32> {
+        let mut lfo = Lfo::default();
+        let beats_per_sample = beats_per_sample(None, SAMPLE_RATE);
+
+        (0..num_blocks)
+            .map(|n| {
+                let pos_beats =
+                    start_beats.map(|pos| pos + (n * BLOCK_LEN) as f64 * beats_per_sample);
+                lfo.advance(
+                    rate,
+                    LfoShape::Sine,
+                    None,
+                    pos_beats,
+                    BLOCK_LEN,
+                    SAMPLE_RATE,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_playhead_locks_every_block_without_moving_it() {
+        let locked = run(LfoRate::Sixteenth, Some(0.), 1000);
+        let free = run(LfoRate::Sixteenth, None, 1000);
+
+        for (n, (locked, free)) in locked.iter().zip(&free).enumerate() {
+            assert!((locked - free).abs() < 1e-4, "[{n}]: {locked} != {free}");
+        }
+    }
+
+    #[test]
+    fn short_blocks_follow_the_shape() {
+        // a sixteenth, at 120 BPM, lasts for 5512.5 samples
+        let values = run(LfoRate::Sixteenth, Some(0.), 172);
+        let max_step = values
+            .windows(2)
+            .fold(0f32, |step, pair| step.max((pair[1] - pair[0]).abs()));
+
+        let step = TAU * BLOCK_LEN as f32 / 5512.5;
+        assert!(max_step <= 1.001 * step, "{max_step}");
+        assert!(values.iter().any(|&v| v > 0.99) && values.iter().any(|&v| v < -0.99));
+    }
+}
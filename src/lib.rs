@@ -1,102 +1,2989 @@
 #![feature(portable_simd)]
+#![cfg_attr(feature = "safe", forbid(unsafe_code))]
 
 extern crate alloc;
 
-use editor::SVFBode;
-use nih_plug_vizia::{create_vizia_editor, ViziaState, ViziaTheming};
+#[cfg(feature = "gui")]
+use nih_plug_vizia::{create_vizia_editor, ViziaTheming};
 use plugin_util::{
     filter::svf::{FilterMode, SVF},
     simd::*,
 };
 
-use nih_plug::prelude::*;
+use nih_plug::{prelude::*, wrapper::state::ParamValue};
+use num::Complex;
+mod audition;
+mod batch;
+mod biquad;
+mod correlation;
+mod denormal;
+mod drive;
+#[cfg(feature = "gui")]
 mod editor;
+mod envelope;
+#[cfg(feature = "gui")]
+mod export;
+mod filter_bank;
+mod follower;
+mod key_track;
+mod lfo;
+#[cfg(feature = "gui")]
+mod matching;
+mod mode;
+mod one_pole;
+mod oversampling;
+mod phase_eq;
+mod phaser;
+mod poly_mod;
+mod precise;
+#[cfg(feature = "gui")]
+mod presets;
+mod ramp;
+mod rms;
+mod rng;
+mod spectrum;
+// deterministic renderers, for checking the DSP against the transfer functions
+#[cfg(any(test, feature = "test-utils"))]
+pub mod sweep;
+// the bare filter, for processing buffers without a host
+#[cfg(any(test, feature = "render"))]
+pub mod render;
+
+#[cfg(feature = "gui")]
+use editor::EditorParams;
+
+#[cfg(not(feature = "gui"))]
+#[derive(Params, Default)]
+struct EditorParams {}
+
+use audition::Audition;
+use batch::BatchFilter;
+use correlation::Correlation;
+use denormal::{DenormalStrategy, ScopedFtz, SilenceGate, ANTI_DENORMAL_NOISE};
+use drive::DriveCharacter;
+use envelope::Adsr;
+use filter_bank::{BankSpacing, FilterBank, MAX_BANDS};
+use follower::Follower;
+use key_track::KeyTracker;
+use lfo::{Lfo, LfoRate, LfoShape};
+use mode::{Mode, ModeFade};
+use one_pole::OnePole;
+use oversampling::{Oversampler, OversamplingFactor, OversamplingQuality, MAX_RATIO};
+use phase_eq::PhaseEq;
+use phaser::{Phaser, MAX_STAGES};
+use poly_mod::{PolyMod, MAX_VOICES};
+use precise::{band_split_prototype, PreciseFilter};
+use ramp::Ramp;
+use rms::Rms;
+use rng::Rng;
+use spectrum::SpectrumTap;
 
 use alloc::sync::Arc;
-use core::{f32::consts::TAU, sync::atomic::Ordering};
+use core::{
+    f32::consts::{PI, TAU},
+    ops::Range,
+    sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+};
+
+/// Range, in Hz, of the cutoff knobs, whose normalized values map onto it logarithmically.
+/// The filter itself stops short of `NYQUIST_MARGIN`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CutoffRange {
+    pub min_hz: f32,
+    pub max_hz: f32,
+}
+
+impl Default for CutoffRange {
+    /// Down into subharmonics, up past the audible range.
+    fn default() -> Self {
+        Self {
+            min_hz: 5.,
+            max_hz: 21000.,
+        }
+    }
+}
+
+impl CutoffRange {
+    /// The range of the states saved up to `LEGACY_CUTOFF_VERSION`, see `Plugin::filter_state`.
+    const LEGACY: Self = Self {
+        min_hz: 13.,
+        max_hz: 21000.,
+    };
+
+    /// The knobs' (normalized) `value`, in Hz, no higher than `max_freq`.
+    pub fn cutoff_hz(self, value: f32, max_freq: f32) -> f32 {
+        (self.min_hz * (self.max_hz / self.min_hz).powf(value)).min(max_freq)
+    }
+
+    /// The inverse of `cutoff_hz`, for frequencies within the range.
+    pub fn normalized(self, hz: f32) -> f32 {
+        ((hz / self.min_hz).ln() / (self.max_hz / self.min_hz).ln()).clamp(0., 1.)
+    }
+
+    /// How many octaves the range spans.
+    pub fn octaves(self) -> f32 {
+        (self.max_hz / self.min_hz).log2()
+    }
+}
+
+/// The last version whose states were saved with `CutoffRange::LEGACY`
+const LEGACY_CUTOFF_VERSION: &str = "0.0.1";
+/// The ids of the parameters set with the cutoff knobs' normalized values
+const CUTOFF_PARAM_IDS: [&str; 3] = ["cutoff", "cutoff_r", "cutoff2"];
+/// Highest cutoff the filter reaches, as a fraction of the sample rate
+const NYQUIST_MARGIN: f32 = 0.45;
+const MIN_RES: f32 = 0.02;
+const MAX_RES: f32 = 1.;
+const BASE_SAMPLE_RATE: f32 = 44100.;
+
+/// The SVF's damping (`1 / Q`) for the editor-side resonance `res`, as taken by the transfer
+/// functions, whose denominator is `s² + 2·res·s + 1`. The audio thread only deals in damping.
+fn res_to_damping(res: f32) -> f32 {
+    2. * res
+}
+
+/// The inverse of `res_to_damping`.
+fn damping_to_res(damping: f32) -> f32 {
+    0.5 * damping
+}
+
+/// The response, at the frequency `w`, in radians per sample, of `num_stages` cascaded SVFs
+/// in `mode`, sharing the gain `gain` between them. `analog_cutoff` is the cutoff's prewarped
+/// analog frequency, see `Prewarp::analog`, and `res` the editor-side resonance.
+pub fn transfer_function(
+    mode: FilterMode,
+    num_stages: usize,
+    w: f32,
+    analog_cutoff: f32,
+    res: f32,
+    gain: f32,
+) -> Complex<f32> {
+    let h = Filter::get_transfer_function::<f32>(mode);
+    let s = Complex::new(0., f32::tan(w * 0.5) / analog_cutoff);
+    h(s, res, gain.powf(1. / num_stages as f32)).powi(num_stages as i32)
+}
+
+/// How far the cutoff, normalized over `range`, `cutoff_norm` and the (editor-side) resonance
+/// `res` are from self-oscillation, at the base sample rate: 0 when the filter's poles reach
+/// the unit circle, 1 when they're as far inside as the maximum damping puts them at that cutoff.
+pub fn stability_margin(range: CutoffRange, cutoff_norm: f32, res: f32) -> f32 {
+    let freq = range.cutoff_hz(cutoff_norm.clamp(0., 1.), NYQUIST_MARGIN * BASE_SAMPLE_RATE);
+    let g = f32::tan(PI * (freq / BASE_SAMPLE_RATE).min(0.499));
+
+    // the bilinear transform of the prototype's upper pole, `-res + j·sqrt(1 - res²)`
+    let pole_radius = |res: f32| {
+        let res = res.clamp(0., MAX_RES);
+        let pole = Complex::new(-res, (1. - res * res).max(0.).sqrt()) * g;
+        ((Complex::from(1.) + pole) / (Complex::from(1.) - pole)).norm()
+    };
+
+    (pole_radius(res).ln() / pole_radius(MAX_RES).ln()).clamp(0., 1.)
+}
+
+const CUTOFF_POLY_MOD_ID: u32 = 0;
+const RES_POLY_MOD_ID: u32 = 1;
+
+/// Below this cutoff, the resonance guard raises the damping floor, inversely to the cutoff.
+const RES_GUARD_FREQ: f32 = 40.;
+
+/// Where the resonance knob's Q holds, when scaling it for a constant bandwidth
+const RES_REF_FREQ: f32 = 1000.;
+
+const NUM_CHANNELS: usize = 2; // stereo
+
+/// Amplitude of the impulse sent into the filter when the editor's "Ping" button is pressed.
+const PING_LEVEL: f32 = 0.5;
+
+/// Number of decimals shown by value readouts, unless changed in the display settings
+const DEFAULT_DECIMALS: usize = 1;
+
+/// Default length of the coefficient ramps, in wall-clock time at any sample rate and block
+/// size. The ramps only ease the coefficients in, they delay nothing, adding no latency.
+const SMOOTHING_MS: f32 = 20.;
+
+/// Shortest coefficient ramp, in samples, however short the smoothing time is set
+const MIN_SMOOTHING_LEN: f32 = 16.;
+
+/// How long the input and output must stay silent before the tail is cut to exact zero
+const SILENCE_HOLD_MS: f32 = 10.;
+
+/// Length of the optional fade-in of the output, after the filters are reset
+const FADE_IN_MS: f32 = 5.;
+/// Window of the level measurements matching the dry output's loudness to the wet one's
+const LEVEL_MATCH_MS: f32 = 300.;
+/// Furthest the dry output's level is matched, either way
+const MAX_LEVEL_MATCH_DB: f32 = 24.;
+/// Length of each half of the output's dip around a filter mode change
+const MODE_FADE_MS: f32 = 2.;
+/// Below this (editor-side) resonance value, the filter is close enough to self-oscillating
+/// that the editor draws a warning, and the oscillation's pitch can be sent out as MIDI.
+const SELF_OSC_RES: f32 = 0.05;
+/// How far, in semitones, the cutoff has to move past the sent note's neighbour to change it
+const PITCH_HYSTERESIS: f32 = 0.1;
+
+/// Below this (editor-side) resonance value, resonance bloom eases into any further decrease
+const BLOOM_RES: f32 = 0.1;
+/// Time constant of resonance bloom
+const BLOOM_MS: f32 = 150.;
+
+/// Dither is scaled for 16-bit targets
+const DITHER_LSB: f32 = 1. / 32768.;
+
+type Filter = SVF<NUM_CHANNELS>;
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ResTaper {
+    #[default]
+    #[name = "Skewed"]
+    Skewed,
+    #[name = "Linear"]
+    Linear,
+    #[name = "Exponential"]
+    Exponential,
+}
+
+/// How the resonance's bandwidth follows the cutoff.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ResScaling {
+    /// the bandwidth, in octaves, stays the same
+    #[default]
+    #[name = "Constant Q"]
+    ConstantQ,
+    /// the bandwidth, in Hz, stays what the resonance would give at `RES_REF_FREQ`
+    #[name = "Constant Bandwidth"]
+    ConstantBandwidth,
+}
+
+/// The path followed by cutoff and resonance as the morph macro goes from 0 to 1.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum MorphPath {
+    /// cutoff up, with a resonance bump mid-travel
+    #[default]
+    #[name = "Open"]
+    Open,
+    /// cutoff up, from its current value all the way to the top
+    #[name = "Sweep"]
+    Sweep,
+    /// cutoff down, more and more resonant
+    #[name = "Close"]
+    Close,
+}
+
+/// What the main output carries. The wet signal always goes to the aux output, if any.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum MainOutput {
+    #[default]
+    #[name = "Wet"]
+    Wet,
+    #[name = "Dry"]
+    Dry,
+}
+
+/// The MIDI controller modulating the cutoff.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum MidiModSource {
+    #[default]
+    #[name = "Mod Wheel"]
+    ModWheel,
+    #[name = "Channel Pressure"]
+    ChannelPressure,
+}
+
+/// How the filter gets from one mode to the next.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ModeSwitch {
+    /// dips the output out and back in, around the switch
+    #[default]
+    #[name = "Crossfade"]
+    Crossfade,
+    /// clears the filter's state on the switch
+    #[name = "Reset"]
+    Reset,
+    /// switches outputs instantly, keeping the state
+    #[name = "Continuous"]
+    Continuous,
+}
+
+/// The final safety stage, keeping the output under the ceiling.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Clipper {
+    #[default]
+    #[name = "Off"]
+    Off,
+    #[name = "Hard"]
+    Hard,
+    /// reaches the ceiling only asymptotically, coloring loud peaks on the way
+    #[name = "Soft"]
+    Soft,
+}
+
+impl Clipper {
+    fn apply(self, frame: f32x2, ceiling: f32) -> f32x2 {
+        let ceiling = Simd::splat(ceiling);
+        match self {
+            Self::Off => frame,
+            Self::Hard => frame.simd_clamp(-ceiling, ceiling),
+            Self::Soft => drive::tanh(frame / ceiling) * ceiling,
+        }
+    }
+}
+
+/// The notes the cutoff can snap to.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Scale {
+    #[default]
+    #[name = "Off"]
+    Off,
+    #[name = "Chromatic"]
+    Chromatic,
+    #[name = "Major"]
+    Major,
+    #[name = "Minor"]
+    Minor,
+    #[name = "Pentatonic"]
+    Pentatonic,
+}
+
+impl Scale {
+    /// Semitones above the root
+    fn degrees(self) -> &'static [f32] {
+        match self {
+            Self::Off => &[],
+            Self::Chromatic => &[0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11.],
+            Self::Major => &[0., 2., 4., 5., 7., 9., 11.],
+            Self::Minor => &[0., 2., 3., 5., 7., 8., 10.],
+            Self::Pentatonic => &[0., 2., 4., 7., 9.],
+        }
+    }
+}
+
+/// Trades CPU for quality, setting at once the oversampling ratio, the oversampling filters'
+/// length, and so their precision, the cutoff's smoothing and the plot's resolution.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Quality {
+    /// each setting is taken from its own parameter
+    #[default]
+    #[name = "Custom"]
+    Custom,
+    /// no oversampling, the shortest (15 taps) oversampling filters, the cutoff's linear
+    /// ramps alone, the plot evaluated in single precision, at its regular density
+    #[name = "Eco"]
+    Eco,
+    /// 2x oversampling, standard (31 taps) filters, linear ramps, the single precision plot
+    #[name = "Normal"]
+    Normal,
+    /// 4x oversampling, standard (31 taps) filters, the cutoff smoothed by a one-pole ahead
+    /// of the ramps, the plot evaluated in double precision, more densely
+    #[name = "High"]
+    High,
+    /// as `High`, with the longest (63 taps) oversampling filters
+    #[name = "Ultra"]
+    Ultra,
+}
+
+/// The settings trading CPU for quality, which `Quality` sets together.
+#[derive(Clone, Copy)]
+struct QualitySettings {
+    oversampling: OversamplingFactor,
+    os_quality: OversamplingQuality,
+    cascade_smoothing: bool,
+    high_res_plot: bool,
+}
+
+impl Quality {
+    fn settings(self) -> Option<QualitySettings> {
+        let (oversampling, os_quality, refined) = match self {
+            Self::Custom => return None,
+            Self::Eco => (OversamplingFactor::X1, OversamplingQuality::Eco, false),
+            Self::Normal => (OversamplingFactor::X2, OversamplingQuality::Standard, false),
+            Self::High => (OversamplingFactor::X4, OversamplingQuality::Standard, true),
+            Self::Ultra => (OversamplingFactor::X4, OversamplingQuality::High, true),
+        };
+
+        Some(QualitySettings {
+            oversampling,
+            os_quality,
+            cascade_smoothing: refined,
+            high_res_plot: refined,
+        })
+    }
+}
+
+/// Where the bilinear transform maps the analog cutoff exactly, for matching other tools.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Prewarp {
+    /// the response at the cutoff matches the analog prototype's
+    #[default]
+    #[name = "At Cutoff"]
+    Cutoff,
+    /// the plain bilinear transform, its cutoff drifting lower towards nyquist
+    #[name = "None"]
+    None,
+}
+
+impl Prewarp {
+    /// The (normalized) analog frequency the transform uses for the cutoff `w_c`, in radians per sample.
+    fn analog(self, w_c: f32) -> f32 {
+        match self {
+            Self::Cutoff => f32::tan(w_c * 0.5),
+            Self::None => w_c * 0.5,
+        }
+    }
+
+    /// The cutoff to hand a (cutoff prewarping) SVF for it to use `analog(w_c)`.
+    fn warp(self, w_c: f32) -> f32 {
+        match self {
+            Self::Cutoff => w_c,
+            Self::None => 2. * f32::atan(self.analog(w_c)),
+        }
+    }
+}
+
+/// The steepness, past the cutoff, of the lowpass and highpass modes: a single SVF for
+/// 12 dB/oct, or a cascade of 2 or 4 identical ones for 24 or 48 dB/oct.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Slope {
+    #[default]
+    #[name = "12 dB/oct"]
+    Db12,
+    #[name = "24 dB/oct"]
+    Db24,
+    #[name = "48 dB/oct"]
+    Db48,
+}
+
+const MAX_CASCADE_STAGES: usize = 4;
+
+/// Frames run through the cascade at once, see `SVFFilter::process_block`
+const CHUNK_LEN: usize = 32;
+
+/// Longest block while the LFO runs, it being sampled once per block
+const LFO_BLOCK_LEN: usize = 32;
+
+impl Slope {
+    /// How many identical SVFs run in series.
+    fn num_stages(self) -> usize {
+        match self {
+            Self::Db12 => 1,
+            Self::Db24 => 2,
+            Self::Db48 => 4,
+        }
+    }
+
+    /// The gain of each stage, for the cascade's to be `gain`.
+    fn stage_gain(self, gain: f32x2) -> f32x2 {
+        let exponent = 1. / self.num_stages() as f32;
+        Simd::from_array(gain.to_array().map(|gain| gain.powf(exponent)))
+    }
+}
+
+/// The integrators' character. Both share the SVF's topology and its stability.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    /// the exact trapezoidal integrators, matching the analog prototype
+    #[default]
+    #[name = "Trapezoidal"]
+    Trapezoidal,
+    /// integrators of limited bandwidth, flattening the cutoff and damping the
+    /// resonance more and more towards the top of the range
+    #[name = "Analog"]
+    Analog,
+}
+
+impl Flavor {
+    /// The `(w_c, damping)` the filter actually gets set to for the cutoff `w_c`,
+    /// in radians per sample at the base rate, and the damping `k`.
+    fn apply(self, w_c: f32x2, k: f32x2) -> (f32x2, f32x2) {
+        const DETUNE: f32 = 0.1;
+        const LOSS: f32 = 0.08;
+
+        match self {
+            Self::Trapezoidal => (w_c, k),
+            Self::Analog => {
+                let t = w_c * Simd::splat(1. / PI);
+                (
+                    w_c * (Simd::splat(1.) - Simd::splat(DETUNE) * t),
+                    k + Simd::splat(LOSS) * t,
+                )
+            }
+        }
+    }
+}
+
+/// What the filter's two lanes process.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ChannelMode {
+    #[default]
+    #[name = "L/R"]
+    LeftRight,
+    /// the left lane filters the mid, the right one, the side
+    #[name = "M/S"]
+    MidSide,
+}
+
+/// Output monitoring, for checking each channel's processing in isolation.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Monitor {
+    #[default]
+    #[name = "Stereo"]
+    Stereo,
+    #[name = "Swap L/R"]
+    Swap,
+    #[name = "Left Only"]
+    Left,
+    #[name = "Right Only"]
+    Right,
+    /// `l - r` on both channels, what the stereo processing does to the image
+    #[name = "Side (L-R)"]
+    Side,
+}
+
+impl Monitor {
+    fn apply(self, frame: f32x2) -> f32x2 {
+        match self {
+            Self::Stereo => frame,
+            Self::Swap => frame.reverse(),
+            Self::Left => Simd::splat(frame[0]),
+            Self::Right => Simd::splat(frame[1]),
+            Self::Side => Simd::splat(frame[0] - frame[1]),
+        }
+    }
+}
+
+/// The effective (mapped and modulated) coefficients last sent to the filter,
+/// readable from any thread without locking.
+#[derive(Default)]
+pub struct SharedCoefficients {
+    w_c: [AtomicF32; NUM_CHANNELS],
+    res: [AtomicF32; NUM_CHANNELS],
+    gain: [AtomicF32; NUM_CHANNELS],
+}
+
+impl SharedCoefficients {
+    fn store(&self, w_c: f32x2, res: f32x2, gain: f32x2) {
+        for (atomics, values) in [(&self.w_c, w_c), (&self.res, res), (&self.gain, gain)] {
+            for (atomic, value) in atomics.iter().zip(values.to_array()) {
+                atomic.store(value, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn load(&self) -> (f32x2, f32x2, f32x2) {
+        let load = |atomics: &[AtomicF32; NUM_CHANNELS]| {
+            Simd::from_array(
+                atomics
+                    .each_ref()
+                    .map(|atomic| atomic.load(Ordering::Relaxed)),
+            )
+        };
+
+        (load(&self.w_c), load(&self.res), load(&self.gain))
+    }
+}
+
+#[derive(Params)]
+struct DisplayParams {
+    #[id = "fill_curve"]
+    fill_curve: BoolParam,
+    #[id = "inverse_curve"]
+    inverse_curve: BoolParam,
+    #[id = "phase_curve"]
+    phase_curve: BoolParam,
+    #[id = "auto_y"]
+    auto_y: BoolParam,
+    #[id = "fixed_axis"]
+    fixed_axis: BoolParam,
+    #[id = "decimals"]
+    decimals: IntParam,
+    #[id = "high_res"]
+    high_res: BoolParam,
+    #[id = "adaptive_density"]
+    adaptive_density: BoolParam,
+    #[id = "phase_saturation"]
+    phase_saturation: FloatParam,
+    #[id = "phase_lightness"]
+    phase_lightness: FloatParam,
+    /// Colors the phase on a blue-orange scale instead of around the color wheel
+    #[id = "phase_colorblind"]
+    phase_colorblind: BoolParam,
+    #[id = "mod_extremes"]
+    mod_extremes: BoolParam,
+    #[id = "spectrum_smoothing"]
+    spectrum_smoothing: FloatParam,
+    #[id = "show_coefficients"]
+    show_coefficients: BoolParam,
+    #[id = "input_spectrum"]
+    input_spectrum: BoolParam,
+}
+
+impl DisplayParams {
+    fn new(decimals: Decimals) -> Self {
+        Self {
+            fill_curve: BoolParam::new("Fill Curve", false).non_automatable(),
+            inverse_curve: BoolParam::new("Show Inverse Curve", false).non_automatable(),
+            phase_curve: BoolParam::new("Show Phase Curve", false).non_automatable(),
+            auto_y: BoolParam::new("Auto Scale Y", false).non_automatable(),
+            fixed_axis: BoolParam::new("Fixed 20 Hz - 20 kHz Axis", false).non_automatable(),
+            decimals: IntParam::new(
+                "Readout Decimals",
+                DEFAULT_DECIMALS as i32,
+                IntRange::Linear { min: 0, max: 3 },
+            )
+            .non_automatable()
+            .with_callback(Arc::new(move |value| {
+                decimals.store(value as usize, Ordering::Relaxed)
+            })),
+            high_res: BoolParam::new("High Resolution Plot", false).non_automatable(),
+            adaptive_density: BoolParam::new("Adaptive Plot Density", false).non_automatable(),
+            phase_saturation: FloatParam::new(
+                "Phase Color Saturation",
+                1.,
+                FloatRange::Linear { min: 0., max: 1. },
+            )
+            .non_automatable(),
+            phase_lightness: FloatParam::new(
+                "Phase Color Lightness",
+                0.5,
+                FloatRange::Linear { min: 0.2, max: 0.8 },
+            )
+            .non_automatable(),
+            phase_colorblind: BoolParam::new("Color-Blind Safe Phase", false).non_automatable(),
+            mod_extremes: BoolParam::new("Show Modulation Extremes", false).non_automatable(),
+            // both averages the spectrum over time, and across neighbouring frequencies
+            spectrum_smoothing: FloatParam::new(
+                "Spectrum Smoothing",
+                0.5,
+                FloatRange::Linear { min: 0., max: 1. },
+            )
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage())
+            .non_automatable(),
+            show_coefficients: BoolParam::new("Show Coefficients", false).non_automatable(),
+            input_spectrum: BoolParam::new("Show Input Spectrum", false).non_automatable(),
+        }
+    }
+}
+
+#[derive(Params)]
+struct SVFParams {
+    two_pi_tick: AtomicF32,
+    /// The cutoff knobs' range, fixed once the parameters are built
+    cutoff_range: CutoffRange,
+    /// Highest cutoff, in Hz, the filter reaches at the current sample rate, shared with the
+    /// cutoff knobs' formatters
+    max_freq: Arc<AtomicF32>,
+    coefficients: SharedCoefficients,
+    phaser_sweep: AtomicF32,
+    /// Channel correlations of the latest block's input and output, for the editor
+    input_correlation: AtomicF32,
+    output_correlation: AtomicF32,
+    envelope_out: AtomicF32,
+    cutoff_mod: AtomicF32,
+    ping: AtomicBool,
+    audition: AtomicBool,
+    non_finite_input: AtomicBool,
+    /// The size of the latest block, for display
+    block_size: AtomicUsize,
+    /// Bumped on every (re)initialization, which also follows every state (preset) load
+    state_generation: AtomicU32,
+    /// Seeds the noise and dither generators when `seed` is left on auto
+    #[persist = "instance_seed"]
+    instance_seed: AtomicU32,
+    spectrum: SpectrumTap,
+    /// The input, before any processing, for the editor's spectrum behind the response
+    input_spectrum: SpectrumTap,
+    #[nested]
+    editor: EditorParams,
+    #[id = "cutoff"]
+    cutoff: FloatParam,
+    #[id = "res"]
+    res: FloatParam,
+    #[id = "link_channels"]
+    link_channels: BoolParam,
+    #[id = "cutoff_r"]
+    cutoff_r: FloatParam,
+    #[id = "res_r"]
+    res_r: FloatParam,
+    #[id = "gain_r"]
+    gain_r: FloatParam,
+    #[id = "band2"]
+    band2: BoolParam,
+    #[id = "cutoff2"]
+    cutoff2: FloatParam,
+    #[id = "res2"]
+    res2: FloatParam,
+    #[id = "gain2"]
+    gain2: FloatParam,
+    #[id = "mode2"]
+    mode2: EnumParam<Mode>,
+    #[id = "band2_input_gain"]
+    band2_input_gain: FloatParam,
+    #[id = "band2_gain"]
+    band2_gain: FloatParam,
+    #[id = "res_taper"]
+    res_taper: EnumParam<ResTaper>,
+    #[id = "res_scaling"]
+    res_scaling: EnumParam<ResScaling>,
+    #[id = "res_guard"]
+    res_guard: BoolParam,
+    #[id = "res_guard_knee"]
+    res_guard_knee: FloatParam,
+    #[id = "res_flip"]
+    res_flip: BoolParam,
+    #[id = "snap_scale"]
+    snap_scale: EnumParam<Scale>,
+    #[id = "snap_root"]
+    snap_root: IntParam,
+    #[id = "tuning"]
+    tuning: FloatParam,
+    #[id = "cal_cutoff"]
+    cal_cutoff: FloatParam,
+    #[id = "cal_q"]
+    cal_q: FloatParam,
+    #[id = "gain"]
+    gain: FloatParam,
+    #[id = "invert_response"]
+    invert_response: BoolParam,
+    #[id = "drive"]
+    drive: FloatParam,
+    #[id = "drive_character"]
+    drive_character: EnumParam<DriveCharacter>,
+    #[id = "headroom"]
+    headroom: FloatParam,
+    #[id = "bypass"]
+    bypass: BoolParam,
+    #[id = "mode"]
+    mode: EnumParam<Mode>,
+    #[id = "morph"]
+    morph: FloatParam,
+    #[id = "morph_path"]
+    morph_path: EnumParam<MorphPath>,
+    #[id = "attack"]
+    attack: FloatParam,
+    #[id = "decay"]
+    decay: FloatParam,
+    #[id = "sustain"]
+    sustain: FloatParam,
+    #[id = "release"]
+    release: FloatParam,
+    #[id = "env_depth"]
+    env_depth: FloatParam,
+    #[id = "key_track"]
+    key_track: FloatParam,
+    #[id = "midi_mod_depth"]
+    midi_mod_depth: FloatParam,
+    #[id = "midi_mod_source"]
+    midi_mod_source: EnumParam<MidiModSource>,
+    #[id = "lfo_depth"]
+    lfo_depth: FloatParam,
+    #[id = "lfo_rate"]
+    lfo_rate: EnumParam<LfoRate>,
+    #[id = "lfo_shape"]
+    lfo_shape: EnumParam<LfoShape>,
+    #[id = "glide_time"]
+    glide_time: FloatParam,
+    #[id = "duck_amount"]
+    duck_amount: FloatParam,
+    #[id = "duck_attack"]
+    duck_attack: FloatParam,
+    #[id = "duck_release"]
+    duck_release: FloatParam,
+    #[id = "band1_input_gain"]
+    band1_input_gain: FloatParam,
+    #[id = "band1_gain"]
+    band1_gain: FloatParam,
+    #[id = "width"]
+    width: FloatParam,
+    #[id = "smoothing"]
+    smoothing: FloatParam,
+    #[id = "blend_smoothing"]
+    blend_smoothing: FloatParam,
+    #[id = "dual_mono"]
+    dual_mono: BoolParam,
+    #[id = "channel_mode"]
+    channel_mode: EnumParam<ChannelMode>,
+    #[id = "clear_on_stop"]
+    clear_on_stop: BoolParam,
+    #[id = "raw_params"]
+    raw_params: BoolParam,
+    #[id = "fade_in"]
+    fade_in: BoolParam,
+    #[id = "cascade"]
+    cascade_smoothing: BoolParam,
+    #[id = "db_gain_smoothing"]
+    db_gain_smoothing: BoolParam,
+    #[id = "res_bloom"]
+    res_bloom: BoolParam,
+    #[id = "freeze"]
+    freeze: BoolParam,
+    #[id = "pitch_out"]
+    pitch_out: BoolParam,
+    #[id = "prewarp"]
+    prewarp: EnumParam<Prewarp>,
+    #[id = "flavor"]
+    flavor: EnumParam<Flavor>,
+    #[id = "slope"]
+    slope: EnumParam<Slope>,
+    #[id = "aa"]
+    aa: BoolParam,
+    #[id = "quality"]
+    quality: EnumParam<Quality>,
+    #[id = "oversampling"]
+    oversampling: EnumParam<OversamplingFactor>,
+    #[id = "os_quality"]
+    os_quality: EnumParam<OversamplingQuality>,
+    #[id = "null_test"]
+    null_test: BoolParam,
+    #[id = "denormals"]
+    denormals: EnumParam<DenormalStrategy>,
+    #[id = "double_precision"]
+    double_precision: BoolParam,
+    #[id = "dither"]
+    dither: BoolParam,
+    #[id = "seed"]
+    seed: IntParam,
+    #[id = "monitor"]
+    monitor: EnumParam<Monitor>,
+    #[id = "main_output"]
+    main_output: EnumParam<MainOutput>,
+    #[id = "mode_switch"]
+    mode_switch: EnumParam<ModeSwitch>,
+    #[id = "clipper"]
+    clipper: EnumParam<Clipper>,
+    #[id = "ceiling"]
+    ceiling: FloatParam,
+    #[id = "match_dry"]
+    match_dry: BoolParam,
+    #[id = "send_mode"]
+    send_mode: BoolParam,
+    #[id = "band_split"]
+    band_split: BoolParam,
+    #[id = "res_gain_comp"]
+    res_gain_comp: BoolParam,
+    #[id = "cv_depth"]
+    cv_depth: FloatParam,
+    #[id = "phase_eq"]
+    phase_eq: FloatParam,
+    #[id = "bank_bands"]
+    bank_bands: IntParam,
+    #[id = "bank_spacing"]
+    bank_spacing: EnumParam<BankSpacing>,
+    #[id = "phaser_stages"]
+    phaser_stages: IntParam,
+    #[id = "phaser_rate"]
+    phaser_rate: FloatParam,
+    #[id = "phaser_depth"]
+    phaser_depth: FloatParam,
+    #[id = "phaser_feedback"]
+    phaser_feedback: FloatParam,
+    #[nested(group = "Display")]
+    display: DisplayParams,
+}
+
+/// The number of decimals shown by value readouts, shared with their formatters.
+type Decimals = Arc<AtomicUsize>;
+
+/// Formats `map(value)` with the currently selected number of decimals.
+fn v2s_decimals(
+    decimals: &Decimals,
+    map: impl Fn(f32) -> f32 + Send + Sync + 'static,
+) -> Arc<dyn Fn(f32) -> String + Send + Sync> {
+    let decimals = decimals.clone();
+    Arc::new(move |value| format!("{:.*}", decimals.load(Ordering::Relaxed), map(value)))
+}
+
+/// Shows the cutoff in Hz, or kHz from 1 kHz up, as high as the filter reaches at the
+/// current sample rate, `max_freq`, and reads either back.
+fn cutoff_param(
+    name: &str,
+    decimals: &Decimals,
+    range: CutoffRange,
+    max_freq: &Arc<AtomicF32>,
+) -> FloatParam {
+    let decimals = decimals.clone();
+    let max_freq = max_freq.clone();
+    let parse_hz = formatters::s2v_f32_hz_then_khz();
+
+    FloatParam::new(name, 0.5, FloatRange::Linear { min: 0., max: 1. })
+        .with_value_to_string(Arc::new(move |value| {
+            let decimals = decimals.load(Ordering::Relaxed);
+            let hz = range.cutoff_hz(value, max_freq.load(Ordering::Relaxed));
+            if hz >= 1000. {
+                format!("{:.*} kHz", decimals + 1, hz / 1000.)
+            } else {
+                format!("{hz:.decimals$} Hz")
+            }
+        }))
+        .with_string_to_value(Arc::new(move |string| {
+            Some(range.normalized(parse_hz(string)?))
+        }))
+}
+
+fn res_param(name: &str) -> FloatParam {
+    FloatParam::new(
+        name,
+        1.,
+        FloatRange::Reversed(&FloatRange::Skewed {
+            min: MIN_RES,
+            max: MAX_RES,
+            factor: 0.37,
+        }),
+    )
+}
+
+fn gain_param(name: &str, decimals: &Decimals) -> FloatParam {
+    FloatParam::new(
+        name,
+        0.,
+        FloatRange::Linear {
+            min: -30.,
+            max: 30.,
+        },
+    )
+    .with_unit(" db")
+    .with_value_to_string(v2s_decimals(decimals, |value| value))
+}
+
+fn trim_param(name: &str, decimals: &Decimals) -> FloatParam {
+    FloatParam::new(
+        name,
+        util::db_to_gain(0.),
+        FloatRange::Skewed {
+            min: util::db_to_gain(-24.),
+            max: util::db_to_gain(24.),
+            factor: FloatRange::gain_skew_factor(-24., 24.),
+        },
+    )
+    .with_unit(" dB")
+    .with_value_to_string(v2s_decimals(decimals, util::gain_to_db))
+    .with_string_to_value(formatters::s2v_f32_gain_to_db())
+}
+
+fn envelope_time_param(name: &str, default: f32) -> FloatParam {
+    FloatParam::new(
+        name,
+        default,
+        FloatRange::Skewed {
+            min: 0.1,
+            max: 10000.,
+            factor: FloatRange::skew_factor(-2.),
+        },
+    )
+    .with_unit(" ms")
+}
+
+impl Default for SVFParams {
+    fn default() -> Self {
+        let decimals = Arc::new(AtomicUsize::new(DEFAULT_DECIMALS));
+        let cutoff_range = CutoffRange::default();
+        let max_freq = Arc::new(AtomicF32::new(cutoff_range.max_hz));
+        let cutoff_param = |name| cutoff_param(name, &decimals, cutoff_range, &max_freq);
+
+        Self {
+            two_pi_tick: AtomicF32::new(TAU / BASE_SAMPLE_RATE),
+            cutoff_range,
+            max_freq: max_freq.clone(),
+            coefficients: SharedCoefficients::default(),
+            phaser_sweep: AtomicF32::new(0.),
+            input_correlation: AtomicF32::new(0.),
+            output_correlation: AtomicF32::new(0.),
+            envelope_out: AtomicF32::new(0.),
+            cutoff_mod: AtomicF32::new(0.),
+            ping: AtomicBool::new(false),
+            audition: AtomicBool::new(false),
+            non_finite_input: AtomicBool::new(false),
+            block_size: AtomicUsize::new(0),
+            state_generation: AtomicU32::new(0),
+            instance_seed: AtomicU32::new(rng::instance_seed()),
+            spectrum: SpectrumTap::default(),
+            input_spectrum: SpectrumTap::default(),
+            editor: EditorParams::default(),
+            cutoff: cutoff_param("Cutoff").with_poly_modulation_id(CUTOFF_POLY_MOD_ID),
+            res: res_param("Resonance").with_poly_modulation_id(RES_POLY_MOD_ID),
+
+            // when unlinked, the right channel has its own cutoff, resonance and gain
+            link_channels: BoolParam::new("Link Channels", true),
+            cutoff_r: cutoff_param("Right Cutoff"),
+            res_r: res_param("Right Resonance"),
+            gain_r: gain_param("Right Gain", &decimals),
+
+            // a second, simpler, filter after the first, e.g. for a cut and a boost at once
+            band2: BoolParam::new("Band 2", false),
+            cutoff2: cutoff_param("Band 2 Cutoff"),
+            res2: res_param("Band 2 Resonance"),
+            gain2: gain_param("Band 2 Gain", &decimals),
+            mode2: EnumParam::new("Band 2 Mode", Mode::Bell),
+            band2_input_gain: trim_param("Band 2 Input Trim", &decimals),
+            band2_gain: trim_param("Band 2 Output Trim", &decimals),
+
+            res_taper: EnumParam::new("Resonance Taper", ResTaper::default()).non_automatable(),
+            res_scaling: EnumParam::new("Resonance Scaling", ResScaling::default())
+                .non_automatable(),
+
+            // keeps subsonic ringing bounded at the lowest cutoffs
+            res_guard: BoolParam::new("Low Cutoff Resonance Guard", true).non_automatable(),
+            // how far, around the guard's frequency, it fades in
+            res_guard_knee: FloatParam::new(
+                "Resonance Guard Knee",
+                1.,
+                FloatRange::Linear { min: 0., max: 3. },
+            )
+            .with_unit(" oct")
+            .non_automatable(),
+
+            // the resonance knob controls damping, flipping it makes turning it up add damping instead
+            res_flip: BoolParam::new("Flip Resonance Direction", false).non_automatable(),
+
+            // quantizes the cutoff to the scale's notes, for tuning resonant settings to a key
+            snap_scale: EnumParam::new("Cutoff Snap Scale", Scale::default()),
+            snap_root: IntParam::new("Cutoff Snap Root", 0, IntRange::Linear { min: 0, max: 11 })
+                .with_value_to_string(Arc::new(|value| {
+                    const NAMES: [&str; 12] = [
+                        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+                    ];
+                    NAMES[value as usize].into()
+                })),
+            tuning: FloatParam::new(
+                "Tuning",
+                440.,
+                FloatRange::Linear {
+                    min: 415.,
+                    max: 466.,
+                },
+            )
+            .with_unit(" Hz")
+            .non_automatable(),
+
+            // calibration, for matching a specific hardware unit's tuning and damping
+            cal_cutoff: FloatParam::new(
+                "Cutoff Calibration",
+                0.,
+                FloatRange::Linear {
+                    min: -100.,
+                    max: 100.,
+                },
+            )
+            .with_unit(" ct")
+            .non_automatable(),
+            cal_q: FloatParam::new(
+                "Damping Calibration",
+                1.,
+                FloatRange::Skewed {
+                    min: 0.5,
+                    max: 2.,
+                    factor: FloatRange::skew_factor(-1.),
+                },
+            )
+            .with_unit("x")
+            .non_automatable(),
+
+            gain: gain_param("Gain", &decimals),
+
+            // undoes the same settings' boost or cut, other modes have no stable inverse
+            invert_response: BoolParam::new("Invert Response", false),
+
+            drive: FloatParam::new(
+                "Drive",
+                util::db_to_gain(0.),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(0.),
+                    max: util::db_to_gain(24.),
+                    factor: FloatRange::gain_skew_factor(0., 24.),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(20.))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(1))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            drive_character: EnumParam::new("Drive Character", DriveCharacter::default()),
+            // the (driven) level the saturation starts bending at
+            headroom: FloatParam::new(
+                "Headroom",
+                util::db_to_gain(0.),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-18.),
+                    max: util::db_to_gain(18.),
+                    factor: FloatRange::gain_skew_factor(-18., 18.),
+                },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(1))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+
+            // crossfades to the input, the filters still running underneath
+            bypass: BoolParam::new("Bypass", false)
+                .with_value_to_string(formatters::v2s_bool_bypass())
+                .with_string_to_value(formatters::s2v_bool_bypass())
+                .make_bypass(),
+
+            mode: EnumParam::new("Filter Mode", Mode::default()),
+
+            morph: FloatParam::new("Morph", 0., FloatRange::Linear { min: 0., max: 1. })
+                .with_unit("%")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+            morph_path: EnumParam::new("Morph Path", MorphPath::default()).non_automatable(),
+
+            attack: envelope_time_param("Env Attack", 5.),
+            decay: envelope_time_param("Env Decay", 300.),
+            sustain: FloatParam::new("Env Sustain", 0.5, FloatRange::Linear { min: 0., max: 1. }),
+            release: envelope_time_param("Env Release", 300.),
+
+            env_depth: FloatParam::new("Env Depth", 0., FloatRange::Linear { min: -8., max: 8. })
+                .with_unit(" oct"),
+
+            key_track: FloatParam::new("Key Tracking", 0., FloatRange::Linear { min: 0., max: 1. })
+                .with_unit("%")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            midi_mod_depth: FloatParam::new(
+                "MIDI Mod Depth",
+                0.,
+                FloatRange::Linear { min: -8., max: 8. },
+            )
+            .with_unit(" oct"),
+            midi_mod_source: EnumParam::new("MIDI Mod Source", MidiModSource::default())
+                .non_automatable(),
+
+            lfo_depth: FloatParam::new("LFO Depth", 0., FloatRange::Linear { min: 0., max: 4. })
+                .with_unit(" oct"),
+            lfo_rate: EnumParam::new("LFO Rate", LfoRate::default()),
+            lfo_shape: EnumParam::new("LFO Shape", LfoShape::default()),
+
+            glide_time: FloatParam::new(
+                "Glide Time",
+                0.,
+                FloatRange::Skewed {
+                    min: 0.,
+                    max: 5000.,
+                    factor: FloatRange::skew_factor(-2.),
+                },
+            )
+            .with_unit(" ms"),
+
+            duck_amount: FloatParam::new(
+                "Duck Amount",
+                0.,
+                FloatRange::Linear { min: 0., max: 1. },
+            )
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            duck_attack: envelope_time_param("Duck Attack", 10.),
+            duck_release: envelope_time_param("Duck Release", 150.),
+
+            band1_input_gain: trim_param("Band 1 Input Trim", &decimals),
+            band1_gain: trim_param("Band 1 Output Trim", &decimals),
+
+            width: FloatParam::new("Width", 1., FloatRange::Linear { min: 0., max: 2. })
+                .with_unit("%")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // ramp time of the filter's coefficients, lengthened for slower, smoother sweeps
+            smoothing: FloatParam::new(
+                "Smoothing",
+                SMOOTHING_MS,
+                FloatRange::Skewed {
+                    min: 0.1,
+                    max: 500.,
+                    factor: FloatRange::skew_factor(-1.),
+                },
+            )
+            .with_unit(" ms")
+            .non_automatable(),
+
+            // ramp time of the trim and width, independent of the filter's coefficients
+            blend_smoothing: FloatParam::new(
+                "Blend Smoothing",
+                20.,
+                FloatRange::Skewed {
+                    min: 1.,
+                    max: 500.,
+                    factor: FloatRange::skew_factor(-1.),
+                },
+            )
+            .with_unit(" ms")
+            .non_automatable(),
+
+            // the SVF's lanes never share state, this also unlinks everything around them
+            dual_mono: BoolParam::new("Dual Mono", false).non_automatable(),
+            channel_mode: EnumParam::new("Channel Mode", ChannelMode::default()).non_automatable(),
+
+            clear_on_stop: BoolParam::new("Clear On Stop", false),
+
+            // for hosts that already smooth their automation
+            raw_params: BoolParam::new("Raw Parameters", false).non_automatable(),
+
+            // tames the transient of resonant settings "charging up" from silence
+            fade_in: BoolParam::new("Fade In After Reset", false).non_automatable(),
+
+            // for very slow sweeps, where the ramps' corners could be heard
+            cascade_smoothing: BoolParam::new("Cascaded Cutoff Smoothing", false).non_automatable(),
+
+            // perceptually even gain sweeps, for the shelves and the bell
+            db_gain_smoothing: BoolParam::new("Smooth Gain In dB", false).non_automatable(),
+
+            // self-oscillation blooms in gradually, instead of snapping on
+            res_bloom: BoolParam::new("Resonance Bloom", false).non_automatable(),
+
+            // cuts the filter off its input and removes its damping, sustaining whatever
+            // it's ringing with at the cutoff it had when engaged
+            freeze: BoolParam::new("Freeze", false),
+
+            // sends the self-oscillation's pitch as MIDI notes, for other instruments to follow
+            pitch_out: BoolParam::new("Self-Oscillation MIDI Out", false).non_automatable(),
+
+            prewarp: EnumParam::new("Prewarping", Prewarp::default()).non_automatable(),
+            flavor: EnumParam::new("Flavor", Flavor::default()).non_automatable(),
+            slope: EnumParam::new("Slope", Slope::default()).non_automatable(),
+
+            aa: BoolParam::new("Anti-Aliasing", false),
+
+            // overrides the oversampling, cutoff smoothing and plot resolution settings,
+            // unless `Custom`
+            quality: EnumParam::new("Quality", Quality::default()).non_automatable(),
+
+            oversampling: EnumParam::new("Oversampling", OversamplingFactor::default())
+                .non_automatable(),
+            os_quality: EnumParam::new("Oversampling Quality", OversamplingQuality::default())
+                .non_automatable(),
+
+            // dev tool, outputs `wet - dry`, hidden from the host to avoid accidents
+            null_test: BoolParam::new("Null Test", false).hide().non_automatable(),
+
+            denormals: EnumParam::new("Denormal Protection", DenormalStrategy::default())
+                .non_automatable(),
+
+            // only while rendering offline, where the extra cost doesn't matter
+            double_precision: BoolParam::new("Offline Double Precision", false).non_automatable(),
+
+            dither: BoolParam::new("Dither", false),
+
+            // 0 picks this instance's own seed, so that instances never generate the same noise
+            seed: IntParam::new("Random Seed", 0, IntRange::Linear { min: 0, max: 9999 })
+                .non_automatable()
+                .with_value_to_string(Arc::new(|value| match value {
+                    0 => "Auto".into(),
+                    value => value.to_string(),
+                })),
+
+            monitor: EnumParam::new("Monitor", Monitor::default()).non_automatable(),
+
+            main_output: EnumParam::new("Main Output", MainOutput::default()).non_automatable(),
+            mode_switch: EnumParam::new("Mode Switch", ModeSwitch::default()).non_automatable(),
+
+            clipper: EnumParam::new("Output Clipper", Clipper::default()),
+            ceiling: FloatParam::new(
+                "Output Ceiling",
+                util::db_to_gain(0.),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-24.),
+                    max: util::db_to_gain(0.),
+                    factor: FloatRange::gain_skew_factor(-24., 0.),
+                },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(v2s_decimals(&decimals, util::gain_to_db))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+
+            // for unbiased A/B comparisons, the dry main output takes the wet one's loudness
+            match_dry: BoolParam::new("Level-Matched Dry", false).non_automatable(),
+
+            // for sends: the main output is guaranteed to be 100% wet, whatever else is set
+            send_mode: BoolParam::new("Send Mode", false).non_automatable(),
+
+            band_split: BoolParam::new("Band Split", false),
+
+            // keeps the resonant peak at (around) unity, instead of the passband
+            res_gain_comp: BoolParam::new("Resonance Gain Compensation", false),
+
+            // octaves of cutoff offset per unit of the aux input's signal
+            cv_depth: FloatParam::new("CV Depth", 0., FloatRange::Linear { min: -8., max: 8. })
+                .with_unit(" oct"),
+
+            // 0 bypasses the allpass correction stage entirely
+            phase_eq: FloatParam::new(
+                "Phase Correction",
+                0.,
+                FloatRange::Linear { min: 0., max: 1. },
+            )
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            bank_bands: IntParam::new(
+                "Bank Bands",
+                0,
+                IntRange::Linear {
+                    min: 0,
+                    max: MAX_BANDS as i32,
+                },
+            )
+            .with_value_to_string(Arc::new(|value| match value {
+                0 => "Off".to_string(),
+                n => n.to_string(),
+            })),
+            bank_spacing: EnumParam::new("Bank Spacing", BankSpacing::default()),
+
+            phaser_stages: IntParam::new(
+                "Phaser Stages",
+                0,
+                IntRange::Linear {
+                    min: 0,
+                    max: MAX_STAGES as i32,
+                },
+            )
+            .with_value_to_string(Arc::new(|value| match value {
+                0 => "Off".to_string(),
+                n => n.to_string(),
+            })),
+
+            phaser_rate: FloatParam::new(
+                "Phaser Rate",
+                0.5,
+                FloatRange::Skewed {
+                    min: 0.01,
+                    max: 10.,
+                    factor: FloatRange::skew_factor(-2.),
+                },
+            )
+            .with_unit(" Hz"),
+
+            phaser_depth: FloatParam::new(
+                "Phaser Depth",
+                2.,
+                FloatRange::Linear { min: 0., max: 4. },
+            )
+            .with_unit(" oct"),
+
+            phaser_feedback: FloatParam::new(
+                "Phaser Feedback",
+                0.,
+                FloatRange::Linear {
+                    min: -0.95,
+                    max: 0.95,
+                },
+            ),
+
+            display: DisplayParams::new(decimals),
+        }
+    }
+}
+
+impl SVFParams {
+    /// The resonance knob's value, mapped through the selected taper. All tapers
+    /// cover the same range, from `MAX_RES` (no resonance) down to `MIN_RES`.
+    fn res_value(&self) -> f32 {
+        self.res_value_at(self.res.unmodulated_normalized_value())
+    }
+
+    /// The cutoff, resonance and gain knobs of `channel`, the main ones unless unlinked.
+    fn channel_knobs(&self, channel: usize) -> [&FloatParam; 3] {
+        if channel == 0 || self.link_channels.value() {
+            [&self.cutoff, &self.res, &self.gain]
+        } else {
+            [&self.cutoff_r, &self.res_r, &self.gain_r]
+        }
+    }
+
+    /// `res_value`, for the resonance knob at the normalized value `norm`.
+    fn res_value_at(&self, norm: f32) -> f32 {
+        let norm = self.res_direction(norm);
+        match self.res_taper.value() {
+            ResTaper::Skewed => self.res.preview_plain(norm),
+            ResTaper::Linear => MAX_RES + (MIN_RES - MAX_RES) * norm,
+            ResTaper::Exponential => MAX_RES * (MIN_RES / MAX_RES).powf(norm),
+        }
+    }
+
+    /// The inverse of `res_value`: the resonance knob's normalized value for `res`.
+    #[cfg(feature = "gui")]
+    fn res_normalized(&self, res: f32) -> f32 {
+        self.res_direction(match self.res_taper.value() {
+            ResTaper::Skewed => self.res.preview_normalized(res),
+            ResTaper::Linear => (res - MAX_RES) / (MIN_RES - MAX_RES),
+            ResTaper::Exponential => (res / MAX_RES).ln() / (MIN_RES / MAX_RES).ln(),
+        })
+    }
+
+    /// The seed of the noise and dither generators.
+    fn seed(&self) -> u32 {
+        match self.seed.value() {
+            0 => self.instance_seed.load(Ordering::Relaxed),
+            seed => seed as u32,
+        }
+    }
+
+    /// Flips the resonance knob's normalized value, if its direction is flipped. Its own inverse.
+    fn res_direction(&self, norm: f32) -> f32 {
+        if self.res_flip.value() {
+            1. - norm
+        } else {
+            norm
+        }
+    }
+
+    /// Per channel, the biquad coefficients `[b0, b1, b2, a1, a2]` equivalent to the
+    /// coefficients last sent to the filter, at the base sample rate.
+    fn biquad_coeffs(&self) -> [[f32; 5]; NUM_CHANNELS] {
+        let (w_c, res, gain) = self.coefficients.load();
+        let mode = self.mode.modulated_plain_value();
+
+        let prewarp = self.prewarp.value();
+        let (w_c, res) = self.flavor.value().apply(w_c, res);
+
+        core::array::from_fn(|i| biquad::coefficients(mode, prewarp.warp(w_c[i]), res[i], gain[i]))
+    }
+
+    /// The (editor-side) resonance the filter runs at, for the knob's `res` at `cutoff_hz`.
+    /// For a constant bandwidth, the Q grows in proportion to the cutoff.
+    fn scaled_res(&self, res: f32, cutoff_hz: f32) -> f32 {
+        match self.res_scaling.value() {
+            ResScaling::ConstantQ => res,
+            ResScaling::ConstantBandwidth => {
+                (res * RES_REF_FREQ / cutoff_hz).clamp(MIN_RES, MAX_RES)
+            }
+        }
+    }
+
+    /// Attenuation cancelling the peak the resonance `res` gives `mode`'s response, over
+    /// `num_stages` stages, if enabled. Band split mode is left as is.
+    fn res_compensation(&self, mode: Mode, res: f32, num_stages: usize) -> f32 {
+        if self.res_gain_comp.value() && !self.band_split.value() {
+            mode.resonance_peak(res).powi(-(num_stages as i32))
+        } else {
+            1.
+        }
+    }
+
+    /// The second band's cutoff, in radians per sample, (editor-side) resonance, linear gain
+    /// and mode. It runs at the base sample rate, unaffected by the first's modulations.
+    fn band2_values(&self) -> (f32, f32, f32, Mode) {
+        let two_pi_tick = self.two_pi_tick.load(Ordering::Relaxed);
+        let max_freq = self.max_freq.load(Ordering::Relaxed);
+        let cutoff = self
+            .cutoff_range
+            .cutoff_hz(self.cutoff2.modulated_plain_value(), max_freq);
+
+        (
+            cutoff * two_pi_tick,
+            self.res2.modulated_plain_value(),
+            util::db_to_gain(self.gain2.modulated_plain_value()),
+            self.mode2.modulated_plain_value(),
+        )
+    }
+
+    /// The second band's response, trims included, at the frequency `w`, in radians per
+    /// sample, 1 if disabled.
+    fn band2_response(&self, w: f32) -> Complex<f32> {
+        if !self.band2.value() {
+            return Complex::from(1.);
+        }
+
+        let (w_c, res, gain, mode) = self.band2_values();
+        transfer_function(mode.filter_mode(), 1, w, f32::tan(w_c * 0.5), res, gain)
+            * mode.output_scale(gain)
+            * self.band2_input_gain.unmodulated_plain_value()
+            * self.band2_gain.unmodulated_plain_value()
+    }
+
+    /// The gain the filter runs at, in dB, for the `gain` parameter's value `gain_db`. The
+    /// gain modes' responses, inverted, are the same modes' with the opposite gains.
+    fn effective_gain_db(&self, gain_db: f32) -> f32 {
+        if self.invert_response.value() && self.mode.modulated_plain_value().uses_gain() {
+            -gain_db
+        } else {
+            gain_db
+        }
+    }
+
+    /// The settings in effect, either those of the quality level, or the individual ones.
+    fn quality_settings(&self) -> QualitySettings {
+        self.quality
+            .value()
+            .settings()
+            .unwrap_or_else(|| QualitySettings {
+                oversampling: self.oversampling.value(),
+                os_quality: self.os_quality.value(),
+                cascade_smoothing: self.cascade_smoothing.value(),
+                high_res_plot: self.display.high_res.value(),
+            })
+    }
+
+    /// The normalized cutoff and (editor-side) resonance, moved along the morph path.
+    fn morphed_values(&self) -> (f32, f32) {
+        self.morph(self.cutoff.unmodulated_plain_value(), self.res_value())
+    }
+
+    /// `morphed_values`, for the knobs of `channel`, with normalized offsets applied to the
+    /// cutoff and resonance.
+    fn offset_morphed_values(
+        &self,
+        channel: usize,
+        cutoff_offset: f32,
+        res_offset: f32,
+    ) -> (f32, f32) {
+        let [cutoff, res, _] = self.channel_knobs(channel);
+        let cutoff = (cutoff.modulated_normalized_value() + cutoff_offset).clamp(0., 1.);
+        let res_norm = (res.modulated_normalized_value() + res_offset).clamp(0., 1.);
+        self.morph(
+            self.cutoff.preview_plain(cutoff),
+            self.res_value_at(res_norm),
+        )
+    }
+
+    /// Moves the normalized `cutoff` to the nearest note of the snapping scale, if any.
+    fn snap_cutoff(&self, cutoff: f32) -> f32 {
+        let degrees = self.snap_scale.value().degrees();
+        if degrees.is_empty() {
+            return cutoff;
+        }
+
+        let hz = self
+            .cutoff_range
+            .cutoff_hz(cutoff, self.cutoff_range.max_hz);
+        let tuning = self.tuning.value();
+        // the root, in semitones relative to A
+        let root = self.snap_root.value() as f32 - 9.;
+        let pitch = 12. * f32::log2(hz / tuning) - root;
+
+        let octave = (pitch / 12.).floor();
+        let within = pitch - 12. * octave;
+        let degree = degrees
+            .iter()
+            .chain([&12.])
+            .copied()
+            .min_by(|a, b| (a - within).abs().total_cmp(&(b - within).abs()))
+            .unwrap_or(0.);
+
+        let snapped = tuning * f32::exp2((12. * octave + degree + root) / 12.);
+        self.cutoff_range.normalized(snapped)
+    }
+
+    /// Moves the normalized `cutoff` and (editor-side) `res` along the morph path,
+    /// then snaps the cutoff and applies the calibration trims.
+    fn morph(&self, cutoff: f32, res: f32) -> (f32, f32) {
+        let amount = self.morph.unmodulated_plain_value();
+
+        // moves the resonance towards `MIN_RES`, `depth` being the (logarithmic) fraction of the way
+        let bump = |depth: f32| res * (MIN_RES / res).powf(depth);
+
+        let (cutoff, res) = match self.morph_path.value() {
+            MorphPath::Open => (
+                (cutoff + 0.5 * amount).min(1.),
+                bump(0.5 * f32::sin(PI * amount)),
+            ),
+            MorphPath::Sweep => (cutoff + (1. - cutoff) * amount, res),
+            MorphPath::Close => ((cutoff - 0.5 * amount).max(0.), bump(0.5 * amount)),
+        };
+
+        let cutoff = self.snap_cutoff(cutoff);
+
+        let octaves = self.cal_cutoff.unmodulated_plain_value() / 1200.;
+        (
+            (cutoff + octaves / self.cutoff_range.octaves()).clamp(0., 1.),
+            (res * self.cal_q.unmodulated_plain_value()).clamp(MIN_RES, MAX_RES),
+        )
+    }
+
+    /// The coefficients for the knobs' current, modulated, values. With sample accurate
+    /// automation, blocks are split wherever automation or modulation changes them, so that
+    /// they're constant over each `process` call.
+    fn get_values(
+        &self,
+        two_pi_tick: f32,
+        cutoff_offset: f32,
+        res_offset: f32,
+    ) -> (f32x2, f32x2, f32x2, FilterMode) {
+        let mut values = [[0.; NUM_CHANNELS]; 3];
+        let max_freq = self.max_freq.load(Ordering::Relaxed);
+
+        for channel in 0..NUM_CHANNELS {
+            let (cutoff_normalized, res) =
+                self.offset_morphed_values(channel, cutoff_offset, res_offset);
+            let gain_db = self.channel_knobs(channel)[2].modulated_plain_value();
+            let gain_normalized = self.effective_gain_db(gain_db);
+
+            values[0][channel] =
+                two_pi_tick * self.cutoff_range.cutoff_hz(cutoff_normalized, max_freq);
+            values[1][channel] = res_to_damping(res);
+            values[2][channel] = 10f32.powf(gain_normalized * (1. / 20.));
+        }
+
+        let [w_c, res, gain] = values.map(Simd::from_array);
+        (
+            w_c,
+            res,
+            gain,
+            self.mode.modulated_plain_value().filter_mode(),
+        )
+    }
+}
+
+/// `max(x, 0)`, with the corner rounded off (quadratically) over a `knee` wide span.
+fn soft_knee(x: f32, knee: f32) -> f32 {
+    if x <= -0.5 * knee {
+        0.
+    } else if x >= 0.5 * knee {
+        x
+    } else {
+        let t = x + 0.5 * knee;
+        t * t / (2. * knee)
+    }
+}
+
+/// The lowest damping the resonance guard lets through at `w_c`, doubling with every octave
+/// below `w_guard`, eased in over `knee` octaves around it.
+fn res_guard_floor(w_c: f32, w_guard: f32, knee: f32) -> f32 {
+    res_to_damping(MIN_RES) * soft_knee((w_guard / w_c).log2(), knee).exp2()
+}
+
+/// `[l + r, l - r]` of the frame `[l, r]`. Twice the mid and side of a left/right frame, and
+/// the left and right of a mid/side one.
+fn mid_side(frame: f32x2) -> f32x2 {
+    let swapped = frame.reverse();
+    (frame + swapped) * Simd::from_array([1., 0.]) + (swapped - frame) * Simd::from_array([0., 1.])
+}
+
+/// Scales the side component of a stereo frame, leaving the mid untouched.
+fn apply_width(frame: f32x2, width: f32) -> f32x2 {
+    let mid = (frame + frame.reverse()) * Simd::splat(0.5);
+    let side = frame - mid;
+    mid + side * Simd::splat(width)
+}
+
+/// Advances a coefficient ramp of `len` samples, with `remaining` left, by a block of
+/// `num_samples`. Returns the increment, and for how many of the block's samples to tick the
+/// smoothers, they must never be ticked past their target. Retargeting with an unchanged
+/// target continues the ongoing ramp instead of restarting it, so that a ramp's length
+/// depends neither on the block size nor on how many (possibly tiny) blocks it spans.
+fn smoothing_step(
+    remaining: &mut f32,
+    len: f32,
+    restart: bool,
+    num_samples: usize,
+) -> (f32, usize) {
+    if restart {
+        *remaining = len;
+    }
+
+    let ramp = remaining.max(1.);
+    let ticks = (*remaining as usize).min(num_samples);
+    *remaining = (*remaining - num_samples as f32).max(0.);
+
+    (1. / ramp, ticks)
+}
+
+/// The prototypes of the cascade's stages, in either precision, `k` being their damping.
+fn precise_prototypes(
+    mode: Mode,
+    band_split: bool,
+    k: f32x2,
+    stage_gain: f32x2,
+) -> [biquad::Prototype; 2] {
+    core::array::from_fn(|i| {
+        if band_split {
+            band_split_prototype(k[i], stage_gain[i] - 1.)
+        } else {
+            biquad::analog_prototype(mode, k[i], stage_gain[i])
+        }
+    })
+}
+
+#[derive(Default)]
+pub struct SVFFilter {
+    params: Arc<SVFParams>,
+    sample_rate: f32,
+    two_pi_tick: f32,
+    /// The top of the cutoff's range, capped below nyquist
+    max_freq: f32,
+    /// Length, in samples, of a smoothing ramp
+    smoothing_len: f32,
+    /// Samples left until the filter's smoothers reach their current targets
+    smoothing_remaining: f32,
+    last_targets: Option<(f32x2, f32x2, f32x2, FilterMode, usize)>,
+    /// `log2` of the cutoff, exponentially smoothed ahead of the filter's own ramps
+    cutoff_stage: [f32; 2],
+    /// The gain, in dB, ramped linearly towards `gain_db_target` ahead of the filter's own ramps
+    gain_db: [f32; 2],
+    gain_db_target: [f32; 2],
+    /// dB per sample
+    gain_db_rate: [f32; 2],
+    /// The damping, eased down past `BLOOM_RES`
+    res_stage: [f32; 2],
+    /// The `(w_c, gain)` held while frozen
+    frozen: Option<(f32x2, f32x2)>,
+    /// The note last sent for the self-oscillation's pitch, if it's still held
+    pitch_note: Option<u8>,
+    /// The cascade, only the first `num_stages` running
+    filters: [BatchFilter; MAX_CASCADE_STAGES],
+    /// The cascade, in double precision, running instead while rendering offline, if enabled
+    precise_filters: [PreciseFilter; MAX_CASCADE_STAGES],
+    /// The second band, at the base rate, and whether it ran in the previous block
+    band2: Filter,
+    band2_active: bool,
+    num_stages: usize,
+    envelope: Adsr,
+    envelope_level: f32,
+    /// One per channel, both following the louder one unless in dual mono
+    followers: [Follower; 2],
+    cutoff_poly_mod: PolyMod,
+    res_poly_mod: PolyMod,
+    key_tracker: KeyTracker,
+    key_octaves: f32,
+    /// Latest value, from 0 to 1, of the controller picked by `midi_mod_source`
+    midi_mod: f32,
+    lfo: Lfo,
+    lfo_octaves: f32,
+    was_playing: bool,
+    phaser: Phaser,
+    phase_eq: PhaseEq,
+    filter_bank: FilterBank,
+    needs_priming: bool,
+    /// Samples left in the output's fade-in
+    fade_in_remaining: usize,
+    fade_in_len: usize,
+    mode_fade: ModeFade,
+    input_trim_ramp: Ramp,
+    trim_ramp: Ramp,
+    band2_input_ramp: Ramp,
+    band2_trim_ramp: Ramp,
+    width_ramp: Ramp,
+    /// From 0, processed, to 1, bypassed
+    bypass_ramp: Ramp,
+    aa_filter: OnePole,
+    last_w_c: f32,
+    oversampler: Oversampler,
+    oversampling_config: Option<(OversamplingFactor, OversamplingQuality)>,
+    noise: Rng,
+    dither_rng: Rng,
+    audition: Audition,
+    silence: SilenceGate,
+    dry_rms: Rms,
+    wet_rms: Rms,
+    /// Set while the host renders offline, where nothing reads the editor-shared state
+    offline: bool,
+    /// The `(mode, w_c, res, gain)` last logged
+    #[cfg(feature = "param-log")]
+    logged_params: Option<(Mode, f32, f32, f32)>,
+}
+
+impl SVFFilter {
+    fn get_param_values(&self) -> (f32x2, f32x2, f32x2, FilterMode) {
+        let (w_c, res, gain, _) = self.params.get_values(
+            self.two_pi_tick,
+            self.cutoff_poly_mod.sum(),
+            self.res_poly_mod.sum(),
+        );
+
+        let octaves = self.cutoff_modulation();
+        let w_min = self.two_pi_tick * self.params.cutoff_range.min_hz;
+        let w_max = self.two_pi_tick * self.max_freq;
+        let w_c =
+            (w_c * Simd::splat(octaves.exp2())).simd_clamp(Simd::splat(w_min), Simd::splat(w_max));
+
+        let res: [f32; NUM_CHANNELS] = core::array::from_fn(|i| {
+            let res = self
+                .params
+                .scaled_res(damping_to_res(res[i]), w_c[i] / self.two_pi_tick);
+            res_to_damping(res)
+        });
+        let res = Simd::from_array(res);
+
+        let res = if self.params.res_guard.value() {
+            let w_guard = self.two_pi_tick * RES_GUARD_FREQ;
+            let knee = self.params.res_guard_knee.value();
+            let floor = w_c
+                .to_array()
+                .map(|w_c| res_guard_floor(w_c, w_guard, knee));
+            res.simd_max(Simd::from_array(floor))
+        } else {
+            res
+        };
+
+        // loud input pulls the resonance back up towards `MAX_RES`
+        let duck_amount = self.params.duck_amount.unmodulated_plain_value();
+        let duck = self
+            .followers
+            .each_ref()
+            .map(|follower| duck_amount * follower.level().min(1.));
+        let res = res + (Simd::splat(res_to_damping(MAX_RES)) - res) * Simd::from_array(duck);
+
+        (w_c, res, gain, self.mode_fade.active().filter_mode())
+    }
+
+    /// Returns the `(w_c, res, gain)` coefficients last sent to the filter. Only updated
+    /// when priming while the host renders offline.
+    pub fn effective_coefficients(&self) -> (f32x2, f32x2, f32x2) {
+        self.params.coefficients.load()
+    }
+
+    /// The filter's response, band 1's trims and further stages aside, at `freq_hz`, for the
+    /// coefficients last sent to it, on the left channel. Evaluated exactly as the editor's plot.
+    pub fn frequency_response(&self, freq_hz: f32) -> Complex<f32> {
+        let params = &self.params;
+        let (w_c, res, gain) = self.effective_coefficients();
+        let mode = params.mode.modulated_plain_value();
+        let two_pi_tick = params.two_pi_tick.load(Ordering::Relaxed);
+        let ratio = params.quality_settings().oversampling.ratio() as f32;
+
+        let (w_c, res) = params.flavor.value().apply(w_c, res);
+        let analog_cutoff = params.prewarp.value().analog(w_c[0] / ratio);
+
+        transfer_function(
+            mode.filter_mode(),
+            params.slope.value().num_stages(),
+            freq_hz * two_pi_tick / ratio,
+            analog_cutoff,
+            damping_to_res(res[0]),
+            gain[0],
+        ) * mode.output_scale(gain[0])
+            * params.res_compensation(
+                mode,
+                damping_to_res(res[0]),
+                params.slope.value().num_stages(),
+            )
+            * params.band2_response(freq_hz * two_pi_tick)
+    }
+
+    /// A one-line summary of the filter's current (effective) settings, e.g. for logging.
+    /// Reads the same shared coefficients as the editor, so it can be called from any thread.
+    pub fn filter_info(&self) -> String {
+        let (w_c, res, gain) = self.effective_coefficients();
+        let mode = self.params.mode.modulated_plain_value();
+        let sample_rate = TAU / self.params.two_pi_tick.load(Ordering::Relaxed);
+
+        // the shared resonance is the damping, i.e. 1 / Q
+        format!(
+            "mode: {}, cutoff: {:.1} Hz, res: {:.3} (Q {:.2}), gain: {:.1} dB, sample rate: {:.0} Hz",
+            Mode::variants()[mode.to_index()],
+            w_c[0] * sample_rate / TAU,
+            damping_to_res(res[0]),
+            res[0].recip(),
+            util::gain_to_db(gain[0]),
+            sample_rate,
+        )
+    }
+
+    /// Returns, per channel, the biquad coefficients `[b0, b1, b2, a1, a2]` equivalent to
+    /// the filter's current settings, at the base sample rate.
+    pub fn to_biquad_coeffs(&self) -> [[f32; 5]; NUM_CHANNELS] {
+        self.params.biquad_coeffs()
+    }
+
+    /// Returns the internal modulation sources' latest values: the envelope's level,
+    /// the phaser LFO's sweep and the total cutoff modulation, both in octaves.
+    pub fn modulation_outputs(&self) -> (f32, f32, f32) {
+        let load = |atomic: &AtomicF32| atomic.load(Ordering::Relaxed);
+        let params = &self.params;
+
+        (
+            load(&params.envelope_out),
+            load(&params.phaser_sweep),
+            load(&params.cutoff_mod),
+        )
+    }
+
+    /// Returns whether non-finite (NaN or Inf) input samples were replaced with
+    /// silence since the last call.
+    pub fn take_non_finite_input(&self) -> bool {
+        self.params.non_finite_input.swap(false, Ordering::Relaxed)
+    }
+
+    /// Total modulation of the cutoff, in octaves.
+    fn cutoff_modulation(&self) -> f32 {
+        self.envelope_level * self.params.env_depth.unmodulated_plain_value()
+            + self.key_octaves
+            + self.midi_mod * self.params.midi_mod_depth.unmodulated_plain_value()
+            + self.lfo_octaves
+    }
+
+    fn store_modulation_outputs(&self) {
+        self.params
+            .envelope_out
+            .store(self.envelope_level, Ordering::Relaxed);
+        self.params
+            .cutoff_mod
+            .store(self.cutoff_modulation(), Ordering::Relaxed);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.two_pi_tick = TAU / sample_rate;
+
+        // the editor only ever reads this value on its own, no ordering with other data is needed
+        self.params
+            .two_pi_tick
+            .store(self.two_pi_tick, Ordering::Relaxed);
+
+        self.max_freq = self
+            .params
+            .cutoff_range
+            .max_hz
+            .min(NYQUIST_MARGIN * sample_rate);
+        self.params.max_freq.store(self.max_freq, Ordering::Relaxed);
+
+        self.update_smoothing_len();
+    }
+
+    /// Some hosts switch sample rates (on device changes) without re-initializing, the
+    /// filters are then primed again at the new one.
+    fn follow_sample_rate(&mut self, sample_rate: f32) {
+        if sample_rate > 0. && sample_rate != self.sample_rate {
+            self.set_sample_rate(sample_rate);
+            self.needs_priming = true;
+        }
+    }
+
+    fn update_smoothing_len(&mut self) {
+        let sample_rate = TAU / self.two_pi_tick;
+        self.smoothing_len = (self.params.smoothing.value() / 1000. * sample_rate)
+            .round()
+            .max(MIN_SMOOTHING_LEN);
+    }
+
+    /// Jumps every filter straight to the current parameter values, so that
+    /// playback doesn't start with a sweep from wherever the smoothers were left.
+    fn prime_filters(&mut self) {
+        self.mode_fade.reset(self.params.mode.value());
+
+        let (w_c, res, gain, _) = self.get_param_values();
+        self.params.coefficients.store(w_c, res, gain);
+        let os_factor = self.params.quality_settings().oversampling;
+        let os_scale = Simd::splat(1. / os_factor.ratio() as f32);
+
+        let (filter_w_c, filter_res) = self.filter_coefficients(w_c, res, os_scale);
+        let stage_gain = self.params.slope.value().stage_gain(gain);
+        let prototypes = precise_prototypes(
+            self.mode_fade.active(),
+            self.params.band_split.value(),
+            filter_res,
+            stage_gain,
+        );
+        for filter in &mut self.filters {
+            filter.update(prototypes, filter_w_c);
+        }
+        for filter in &mut self.precise_filters {
+            filter.update(prototypes, filter_w_c);
+        }
+        let (band2_w_c, band2_res, band2_gain, band2_mode) = self.params.band2_values();
+        Filter::get_update_function(band2_mode.filter_mode())(
+            &mut self.band2,
+            Simd::splat(band2_w_c),
+            Simd::splat(res_to_damping(band2_res)),
+            Simd::splat(band2_gain),
+        );
+        self.cutoff_stage = w_c.to_array().map(f32::log2);
+        self.gain_db = gain.to_array().map(util::gain_to_db);
+        self.gain_db_target = self.gain_db;
+        self.res_stage = res.to_array();
+        self.input_trim_ramp
+            .reset(self.params.band1_input_gain.value());
+        self.trim_ramp.reset(self.params.band1_gain.value());
+        self.band2_input_ramp
+            .reset(self.params.band2_input_gain.value());
+        self.band2_trim_ramp.reset(self.params.band2_gain.value());
+        self.width_ramp.reset(self.params.width.value());
+        self.bypass_ramp
+            .reset(if self.params.bypass.value() { 1. } else { 0. });
+        self.last_targets = None;
+        self.smoothing_remaining = 0.;
+
+        self.phaser
+            .prime(self.params.phaser_depth.unmodulated_plain_value(), w_c, res);
+        self.phase_eq
+            .prime(w_c, res, self.params.phase_eq.unmodulated_plain_value());
+        self.filter_bank
+            .prime(w_c * os_scale, res, self.params.bank_spacing.value());
+
+        self.needs_priming = false;
+    }
+
+    /// Primes the filters if `initialize` or `reset` asked for it, from the parameters as
+    /// they are now, the host having possibly restored a state since, fading the output in.
+    fn prime_if_needed(&mut self) {
+        if !self.needs_priming {
+            return;
+        }
+
+        self.prime_filters();
+
+        if self.params.fade_in.value() {
+            self.fade_in_len = (FADE_IN_MS / 1000. * TAU / self.two_pi_tick) as usize;
+            self.fade_in_remaining = self.fade_in_len;
+        }
+    }
+
+    /// Pulls the anti-aliasing lowpass down from ~20 kHz, by up to 1.5 octaves,
+    /// the more resonant the filter is and the faster its cutoff is moving.
+    fn update_anti_aliasing(&mut self, w_c: f32, num_samples: usize) {
+        let tick = self.two_pi_tick / TAU;
+
+        let resonance = (MAX_RES - self.params.morphed_values().1) / (MAX_RES - MIN_RES);
+
+        let sweep = if self.last_w_c > 0. {
+            let octaves_per_sec = (w_c / self.last_w_c).log2().abs() / (num_samples as f32 * tick);
+            (octaves_per_sec / 50.).min(1.)
+        } else {
+            0.
+        };
+        self.last_w_c = w_c;
+
+        let amount = resonance * (0.5 + 0.5 * sweep);
+        self.aa_filter
+            .set_cutoff(self.two_pi_tick * self.max_freq * (-1.5 * amount).exp2());
+    }
+
+    /// Redesigns the oversampling filters if needed, returning the resulting latency.
+    fn configure_oversampling(&mut self) -> Option<u32> {
+        let settings = self.params.quality_settings();
+        let config = (settings.oversampling, settings.os_quality);
+
+        if self.oversampling_config == Some(config) {
+            return None;
+        }
+
+        self.oversampling_config = Some(config);
+        self.oversampler.set_quality(config.1);
+
+        Some(oversampling::latency(config.0, config.1))
+    }
+
+    /// Key tracking is relative to middle C, where the cutoff knob's value is left as is.
+    fn advance_key_tracking(&mut self, num_samples: usize) {
+        let amount = self.params.key_track.unmodulated_plain_value();
+
+        self.key_octaves = self
+            .key_tracker
+            .advance(num_samples)
+            .map_or(0., |note| amount * (note - 60.) / 12.);
+    }
+
+    /// Sampled once per block, like the other modulations, the filter's ramps smoothing
+    /// its steps, and the saw's jump. While it runs, blocks are at most `LFO_BLOCK_LEN`
+    /// long, see `process`. The playhead's position is that of the buffer's start, each
+    /// block locking to it, offset by where the block starts.
+    fn advance_lfo(&mut self, block: Range<usize>, transport: &Transport) {
+        let pos_beats = transport
+            .pos_beats()
+            .filter(|_| transport.playing)
+            .map(|pos_beats| {
+                let beats_per_sample = lfo::beats_per_sample(transport.tempo, self.sample_rate);
+                pos_beats + block.start as f64 * beats_per_sample
+            });
+
+        let value = self.lfo.advance(
+            self.params.lfo_rate.value(),
+            self.params.lfo_shape.value(),
+            transport.tempo,
+            pos_beats,
+            block.len(),
+            self.sample_rate,
+        );
+        self.lfo_octaves = value * self.params.lfo_depth.unmodulated_plain_value();
+    }
+
+    /// The cutoff and damping the filter itself is set to, the former at its (oversampled)
+    /// rate, and prewarped.
+    fn filter_coefficients(&self, w_c: f32x2, res: f32x2, os_scale: f32x2) -> (f32x2, f32x2) {
+        let prewarp = self.params.prewarp.value();
+        let (w_c, res) = self.params.flavor.value().apply(w_c, res);
+        let w_c = Simd::from_array((w_c * os_scale).to_array().map(|w_c| prewarp.warp(w_c)));
+        (w_c, res)
+    }
+
+    /// Runs `w_c` through a one-pole, in octaves, at the rate of a smoothing ramp. The
+    /// filter's linear ramps then follow it, their corners rounded off into an S-curve.
+    fn cascade_cutoff(&mut self, w_c: f32x2, num_samples: usize) -> f32x2 {
+        // snap once close enough, so that the smoothers can settle
+        const EPSILON: f32 = 1e-5;
+        let coeff = f32::exp(-(num_samples as f32) / self.smoothing_len);
+
+        self.cutoff_stage = core::array::from_fn(|i| {
+            let target = w_c[i].log2();
+            let stage = target + (self.cutoff_stage[i] - target) * coeff;
+            if (stage - target).abs() < EPSILON {
+                target
+            } else {
+                stage
+            }
+        });
+
+        Simd::from_array(self.cutoff_stage.map(f32::exp2))
+    }
+
+    /// Follows the (left lane's) self-oscillation pitch with MIDI notes, one at a time.
+    fn track_pitch(&mut self, w_c: f32x2, res: f32x2, context: &mut impl ProcessContext<Self>) {
+        let oscillating = self.params.pitch_out.value() && res[0] < res_to_damping(SELF_OSC_RES);
+
+        let pitch = 69. + 12. * f32::log2(w_c[0] / self.two_pi_tick / 440.);
+        let note = match self.pitch_note {
+            _ if !oscillating => None,
+            Some(note) if (pitch - note as f32).abs() < 0.5 + PITCH_HYSTERESIS => Some(note),
+            _ => Some(pitch.round().clamp(0., 127.) as u8),
+        };
+
+        if note == self.pitch_note {
+            return;
+        }
+
+        if let Some(note) = self.pitch_note {
+            context.send_event(NoteEvent::NoteOff {
+                timing: 0,
+                voice_id: None,
+                channel: 0,
+                note,
+                velocity: 0.,
+            });
+        }
+        if let Some(note) = note {
+            context.send_event(NoteEvent::NoteOn {
+                timing: 0,
+                voice_id: None,
+                channel: 0,
+                note,
+                velocity: 0.8,
+            });
+        }
+
+        self.pitch_note = note;
+    }
+
+    /// Hands changes of the (left lane's) settings past small thresholds to a background
+    /// thread, for logging. Only ever queues a task, so as not to block.
+    #[cfg(feature = "param-log")]
+    fn log_param_changes(
+        &mut self,
+        w_c: f32x2,
+        res: f32x2,
+        gain: f32x2,
+        context: &mut impl ProcessContext<Self>,
+    ) {
+        const CUTOFF_RATIO: f32 = 1.01;
+        const RES_STEP: f32 = 0.005;
+        const GAIN_STEP_DB: f32 = 0.1;
+
+        let current = (
+            self.mode_fade.active(),
+            w_c[0],
+            damping_to_res(res[0]),
+            gain[0],
+        );
+        let significant = self.logged_params.map_or(true, |(mode, w_c, res, gain)| {
+            mode != current.0
+                || (current.1 / w_c).max(w_c / current.1) > CUTOFF_RATIO
+                || (current.2 - res).abs() > RES_STEP
+                || (util::gain_to_db(current.3) - util::gain_to_db(gain)).abs() > GAIN_STEP_DB
+        });
+
+        if significant {
+            self.logged_params = Some(current);
+            context.execute_background(matching::Task::LogParams {
+                pos_samples: context.transport().pos_samples(),
+                mode: current.0,
+                cutoff_hz: current.1 / self.two_pi_tick,
+                res: current.2,
+                gain_db: util::gain_to_db(current.3),
+            });
+        }
+    }
+
+    /// Lets the damping `res` rise instantly, and fall freely down to `BLOOM_RES`'s, but
+    /// only exponentially past it, towards self-oscillation.
+    fn bloom_res(&mut self, res: f32x2, num_samples: usize) -> f32x2 {
+        const EPSILON: f32 = 1e-5;
+        let threshold = res_to_damping(BLOOM_RES);
+        let coeff =
+            Follower::coefficient(BLOOM_MS / 1000., self.two_pi_tick).powi(num_samples as i32);
+
+        self.res_stage = core::array::from_fn(|i| {
+            let target = res[i];
+            let stage = self.res_stage[i].min(threshold).max(target);
+            let stage = target + (stage - target) * coeff;
+            if stage - target < EPSILON {
+                target
+            } else {
+                stage
+            }
+        });
+
+        Simd::from_array(self.res_stage)
+    }
+
+    /// Ramps the gain linearly in dB, at the rate of a smoothing ramp, one block at a time.
+    fn ramp_gain_db(&mut self, gain: f32x2, num_samples: usize) -> f32x2 {
+        for (i, target) in gain
+            .to_array()
+            .map(util::gain_to_db)
+            .into_iter()
+            .enumerate()
+        {
+            if target != self.gain_db_target[i] {
+                self.gain_db_target[i] = target;
+                self.gain_db_rate[i] = (target - self.gain_db[i]).abs() / self.smoothing_len;
+            }
+
+            let step = self.gain_db_rate[i] * num_samples as f32;
+            self.gain_db[i] = target.clamp(self.gain_db[i] - step, self.gain_db[i] + step);
+        }
+
+        Simd::from_array(self.gain_db.map(util::db_to_gain))
+    }
+
+    /// Returns the smoothing increment for this block, see `smoothing_step`.
+    fn smoothing_increment(&mut self, targets_changed: bool, num_samples: usize) -> (f32, usize) {
+        smoothing_step(
+            &mut self.smoothing_remaining,
+            self.smoothing_len,
+            targets_changed,
+            num_samples,
+        )
+    }
+
+    fn advance_envelope(&mut self, num_samples: usize) {
+        let params = &self.params;
+        let ms_to_s = 1. / 1000.;
+
+        self.envelope_level = self.envelope.advance(
+            num_samples as f32 * self.two_pi_tick / TAU,
+            params.attack.unmodulated_plain_value() * ms_to_s,
+            params.decay.unmodulated_plain_value() * ms_to_s,
+            params.sustain.unmodulated_plain_value(),
+            params.release.unmodulated_plain_value() * ms_to_s,
+        );
+    }
+
+    /// Restarts the noise and dither sequences, so that renders are reproducible.
+    fn seed_rngs(&mut self) {
+        let seed = self.params.seed();
+        self.noise = Rng::new(seed);
+        // 2^32 / φ, decorrelates the two sequences
+        self.dither_rng = Rng::new(seed ^ 0x9E37_79B9);
+    }
+
+    /// Follows the input's peak level over `block`, for ducking the resonance.
+    fn advance_follower(&mut self, buffer: &Buffer, block: Range<usize>) {
+        if self.params.duck_amount.unmodulated_plain_value() == 0. {
+            self.followers.iter_mut().for_each(Follower::reset);
+            return;
+        }
 
-const MIN_FREQ: f32 = 13.;
-const MAX_FREQ: f32 = 21000.;
-const BASE_SAMPLE_RATE: f32 = 44100.;
+        let ms_to_s = 1. / 1000.;
+        let coefficient = |param: &FloatParam| {
+            Follower::coefficient(param.unmodulated_plain_value() * ms_to_s, self.two_pi_tick)
+        };
+        let attack = coefficient(&self.params.duck_attack);
+        let release = coefficient(&self.params.duck_release);
 
-const NUM_CHANNELS: usize = 2; // stereo
+        let dual_mono = self.params.dual_mono.value();
+        let channels = buffer.as_slice_immutable();
+        let (left, right) = (&channels[0], channels.get(1).unwrap_or(&channels[0]));
+        for (&l, &r) in left[block.clone()].iter().zip(&right[block]) {
+            let inputs = if dual_mono {
+                [l, r]
+            } else {
+                [l.abs().max(r.abs()); 2]
+            };
+            for (follower, input) in self.followers.iter_mut().zip(inputs) {
+                follower.process(input, attack, release);
+            }
+        }
+    }
 
-type Filter = SVF<NUM_CHANNELS>;
+    /// Applies a note event, at the start of the block it sits in, see `process`.
+    fn handle_event(
+        &mut self,
+        event: NoteEvent<()>,
+        glide_samples: f32,
+        context: &mut impl ProcessContext<Self>,
+    ) {
+        match event {
+            NoteEvent::NoteOn { note, .. } => {
+                self.envelope.note_on();
+                self.key_tracker.note_on(note, glide_samples);
+            }
+            NoteEvent::NoteOff {
+                timing,
+                voice_id,
+                channel,
+                note,
+                ..
+            } => {
+                self.envelope.note_off();
 
-#[derive(Params)]
-struct SVFParams {
-    two_pi_tick: AtomicF32,
-    #[persist = "editor_state"]
-    vizia_state: Arc<ViziaState>,
-    #[id = "cutoff"]
-    cutoff: FloatParam,
-    #[id = "res"]
-    res: FloatParam,
-    #[id = "gain"]
-    gain: FloatParam,
-    #[id = "mode"]
-    mode: EnumParam<FilterMode>,
-}
+                // we don't have voices of our own, every note ends as soon as it's released
+                if let Some(voice_id) = voice_id {
+                    self.cutoff_poly_mod.remove(voice_id);
+                    self.res_poly_mod.remove(voice_id);
+                }
+                context.send_event(NoteEvent::VoiceTerminated {
+                    timing,
+                    voice_id,
+                    channel,
+                    note,
+                });
+            }
+            // taken once per block, the filter's ramps smooth out the controller's steps
+            NoteEvent::MidiCC { cc: 1, value, .. }
+                if self.params.midi_mod_source.value() == MidiModSource::ModWheel =>
+            {
+                self.midi_mod = value;
+            }
+            NoteEvent::MidiChannelPressure { pressure, .. }
+                if self.params.midi_mod_source.value() == MidiModSource::ChannelPressure =>
+            {
+                self.midi_mod = pressure;
+            }
+            NoteEvent::PolyModulation {
+                voice_id,
+                poly_modulation_id,
+                normalized_offset,
+                ..
+            } => match poly_modulation_id {
+                CUTOFF_POLY_MOD_ID => self.cutoff_poly_mod.set(voice_id, normalized_offset),
+                RES_POLY_MOD_ID => self.res_poly_mod.set(voice_id, normalized_offset),
+                _ => (),
+            },
+            _ => (),
+        }
+    }
 
-impl Default for SVFParams {
-    fn default() -> Self {
-        Self {
-            two_pi_tick: AtomicF32::new(TAU / BASE_SAMPLE_RATE),
-            vizia_state: ViziaState::new(|| (400, 140)),
-            cutoff: FloatParam::new("Cutoff", 0.5, FloatRange::Linear { min: 0., max: 1. })
-                .with_value_to_string(Arc::new(|value| {
-                    (MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(value)).to_string()
-                })),
+    /// Processes the samples of `buffer` in `block`, in which no note starts or ends.
+    fn process_block(
+        &mut self,
+        buffer: &mut Buffer,
+        aux: &mut AuxiliaryBuffers,
+        context: &mut impl ProcessContext<Self>,
+        block: Range<usize>,
+    ) {
+        let num_samples = block.len();
 
-            res: FloatParam::new(
-                "Resonance",
-                1.,
-                FloatRange::Reversed(&FloatRange::Skewed {
-                    min: 0.02,
-                    max: 1.,
-                    factor: 0.37,
-                }),
-            ),
+        self.advance_envelope(num_samples);
+        self.advance_follower(buffer, block.clone());
+        self.advance_key_tracking(num_samples);
+        self.advance_lfo(block.clone(), context.transport());
+        if !self.offline {
+            self.store_modulation_outputs();
+        }
 
-            gain: FloatParam::new(
-                "Gain",
-                0.,
-                FloatRange::Linear {
-                    min: -30.,
-                    max: 30.,
-                },
-            )
-            .with_unit(" db"),
+        self.update_smoothing_len();
+        self.prime_if_needed();
 
-            mode: EnumParam::new("Filter Mode", FilterMode::default()),
+        // when crossfading, mode changes, automated or not, first fade the output out in the
+        // old mode, then switch (at a block boundary) and fade back in
+        let requested_mode = self.params.mode.value();
+        if requested_mode != self.mode_fade.active() {
+            match self.params.mode_switch.value() {
+                ModeSwitch::Crossfade => {}
+                ModeSwitch::Reset => {
+                    self.filters.iter_mut().for_each(BatchFilter::reset);
+                    self.precise_filters
+                        .iter_mut()
+                        .for_each(PreciseFilter::reset);
+                    self.mode_fade.reset(requested_mode);
+                }
+                ModeSwitch::Continuous => self.mode_fade.reset(requested_mode),
+            }
         }
-    }
-}
+        self.mode_fade.request(
+            requested_mode,
+            self.two_pi_tick / TAU * 1000. / MODE_FADE_MS,
+        );
 
-impl SVFParams {
-    fn get_values(&self, two_pi_tick: f32) -> (f32x2, f32x2, f32x2, FilterMode) {
-        let cutoff_normalized = self.cutoff.unmodulated_plain_value();
-        let gain_normalized = self.gain.unmodulated_plain_value();
-        (
-            Simd::splat(two_pi_tick * MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(cutoff_normalized)),
-            Simd::splat(2. * self.res.unmodulated_plain_value()),
-            Simd::splat(10f32.powf(gain_normalized * (1. / 20.))),
-            self.mode.unmodulated_plain_value(),
-        )
-    }
-}
+        let (w_c, res, gain, mode) = self.get_param_values();
+        if !self.offline {
+            self.params.coefficients.store(w_c, res, gain);
+        }
+        self.track_pitch(w_c, res, context);
+        #[cfg(feature = "param-log")]
+        self.log_param_changes(w_c, res, gain, context);
+        let raw = self.params.raw_params.value();
+        let gain = if self.params.db_gain_smoothing.value() && !raw {
+            self.ramp_gain_db(gain, num_samples)
+        } else {
+            self.gain_db = gain.to_array().map(util::gain_to_db);
+            self.gain_db_target = self.gain_db;
+            gain
+        };
+        let active_mode = self.mode_fade.active();
+        let band_split = self.params.band_split.value();
+        let output_scale = gain.to_array().map(|gain| {
+            if band_split {
+                1.
+            } else {
+                active_mode.output_scale(gain)
+            }
+        });
+        let output_scale = Simd::from_array(output_scale);
 
-#[derive(Default)]
-pub struct SVFFilter {
-    params: Arc<SVFParams>,
-    two_pi_tick: f32,
-    smoothing_inc: f32,
-    filter: Filter,
-}
+        let slope = self.params.slope.value();
 
-impl SVFFilter {
-    fn get_param_values(&self) -> (f32x2, f32x2, f32x2, FilterMode) {
-        self.params.get_values(self.two_pi_tick)
+        // the phaser's targets move with its LFO, its ramps are always restarted
+        let inc = Simd::splat(1. / self.smoothing_len.max(num_samples as f32));
+
+        let send_mode = self.params.send_mode.value();
+
+        let clipper = self.params.clipper.value();
+        let ceiling = self.params.ceiling.value();
+
+        let null_test = self.params.null_test.value() && !send_mode;
+
+        let mut ping = self.params.ping.swap(false, Ordering::Relaxed);
+
+        if self.params.audition.swap(false, Ordering::Relaxed) {
+            self.audition.trigger(self.two_pi_tick);
+        }
+
+        let dither = self.params.dither.value();
+
+        let monitor = self.params.monitor.value();
+
+        // nothing crosses between the channels, bar the explicitly chosen monitoring
+        // and mid/side encoding
+        let dual_mono = self.params.dual_mono.value();
+        let mid_side_mode = self.params.channel_mode.value() == ChannelMode::MidSide;
+
+        // the drive stage is bypassed entirely at 0 dB
+        let drive_character = self.params.drive_character.value();
+        let headroom = self.params.headroom.value();
+        let drive_enabled =
+            self.params.drive.value() > 1. || self.params.drive.smoothed.is_smoothing();
+
+        let denormals = self.params.denormals.value();
+        let _ftz = (denormals == DenormalStrategy::FlushToZero).then(ScopedFtz::enable);
+        let anti_denormal = denormals == DenormalStrategy::Noise;
+        let silence_hold = (SILENCE_HOLD_MS / 1000. * TAU / self.two_pi_tick) as usize;
+
+        let aa = self.params.aa.value();
+        if aa {
+            self.update_anti_aliasing(w_c[0], num_samples);
+        }
+
+        let num_phaser_stages = self.params.phaser_stages.value() as usize;
+        let phaser_feedback = self.params.phaser_feedback.unmodulated_plain_value();
+        if num_phaser_stages > 0 {
+            let depth = self.params.phaser_depth.unmodulated_plain_value();
+            let sweep = self.phaser.update(
+                num_samples,
+                self.two_pi_tick,
+                self.params.phaser_rate.unmodulated_plain_value(),
+                depth,
+                w_c,
+                res,
+                inc,
+            );
+            if raw {
+                self.phaser.prime(depth, w_c, res);
+            }
+            self.params.phaser_sweep.store(sweep, Ordering::Relaxed);
+        }
+
+        let phase_eq_amount = self.params.phase_eq.unmodulated_plain_value();
+        let phase_eq = phase_eq_amount > 0.;
+        if phase_eq {
+            if raw {
+                self.phase_eq.prime(w_c, res, phase_eq_amount);
+            } else {
+                self.phase_eq.update(w_c, res, phase_eq_amount, inc);
+            }
+        }
+
+        if let Some(latency) = self.configure_oversampling() {
+            context.set_latency_samples(latency);
+        }
+
+        let quality = self.params.quality_settings();
+        let os_factor = quality.oversampling;
+        let os_ratio = os_factor.ratio();
+        let os_scale = Simd::splat(1. / os_ratio as f32);
+
+        let w_c = if quality.cascade_smoothing && !raw {
+            self.cascade_cutoff(w_c, num_samples)
+        } else {
+            self.cutoff_stage = w_c.to_array().map(f32::log2);
+            w_c
+        };
+
+        let res = if self.params.res_bloom.value() && !raw {
+            self.bloom_res(res, num_samples)
+        } else {
+            self.res_stage = res.to_array();
+            res
+        };
+
+        let (_, flavored_res) = self.params.flavor.value().apply(w_c, res);
+        let res_comp = flavored_res.to_array().map(|k| {
+            self.params
+                .res_compensation(active_mode, damping_to_res(k), slope.num_stages())
+        });
+        let output_scale = output_scale * Simd::from_array(res_comp);
+
+        // without damping, the (trapezoidal) SVF is lossless, and its state circulates
+        // unchanged in energy, the smoothers still easing the damping down
+        let freeze = self.params.freeze.value();
+        let (w_c, res, gain) = if freeze {
+            let &mut (w_c, gain) = self.frozen.get_or_insert((w_c, gain));
+            (w_c, Simd::splat(0.), gain)
+        } else {
+            self.frozen = None;
+            (w_c, res, gain)
+        };
+
+        // stages joining the cascade start from silence, not from wherever they were left
+        let num_stages = slope.num_stages();
+        let joining = self.num_stages.min(num_stages)..num_stages;
+        self.filters[joining.clone()]
+            .iter_mut()
+            .for_each(BatchFilter::reset);
+        self.precise_filters[joining]
+            .iter_mut()
+            .for_each(PreciseFilter::reset);
+        self.num_stages = num_stages;
+        let stage_gain = slope.stage_gain(gain);
+
+        let targets = Some((w_c, res, stage_gain, mode, os_ratio));
+        let targets_changed = self.last_targets != targets;
+        self.last_targets = targets;
+
+        // audio-rate cutoff modulation, from the aux input, sets the filter every sample instead
+        let cv_depth = self.params.cv_depth.value();
+        let mut cv_samples = aux
+            .inputs
+            .first()
+            .filter(|_| cv_depth != 0. && !freeze)
+            .map(|cv| cv.as_slice_immutable()[0][block.clone()].iter());
+        let prewarp = self.params.prewarp.value();
+        let flavor = self.params.flavor.value();
+        let (w_min, w_max) = (
+            self.two_pi_tick * self.params.cutoff_range.min_hz,
+            self.two_pi_tick * self.max_freq,
+        );
+
+        // once the smoothers have reached their (unchanged) targets, ticking them is a no-op
+        let ticking = cv_samples.is_none() && (targets_changed || self.smoothing_remaining > 0.);
+        let mut ticks_left = 0;
+
+        let double_precision = self.offline && self.params.double_precision.value();
+        // in band split mode, the gain only applies to the (unit) band around the cutoff,
+        // as wide as the resonance makes it, everything else passing through untouched
+        let prototypes = |k| precise_prototypes(active_mode, band_split, k, stage_gain);
+
+        let (filter_w_c, filter_res) = self.filter_coefficients(w_c, res, os_scale);
+        if raw {
+            for filter in &mut self.filters {
+                filter.update(prototypes(filter_res), filter_w_c);
+            }
+            for filter in &mut self.precise_filters {
+                filter.update(prototypes(filter_res), filter_w_c);
+            }
+            self.smoothing_remaining = 0.;
+        } else {
+            let (filter_inc, ticks) = self.smoothing_increment(targets_changed, num_samples);
+            let filter_inc = Simd::splat(filter_inc);
+            if ticking {
+                ticks_left = ticks * os_ratio;
+            }
+            for filter in &mut self.filters {
+                filter.smoothing_update(prototypes(filter_res), filter_w_c, filter_inc * os_scale);
+            }
+            for filter in &mut self.precise_filters {
+                filter.smoothing_update(prototypes(filter_res), filter_w_c, filter_inc * os_scale);
+            }
+        }
+
+        // the bank runs alongside the filter, at its rate, to stay aligned with it
+        let bank_bands = self.params.bank_bands.value() as usize;
+        if bank_bands > 0 {
+            let spacing = self.params.bank_spacing.value();
+            if raw {
+                self.filter_bank.prime(w_c * os_scale, res, spacing);
+            } else {
+                self.filter_bank
+                    .update(w_c * os_scale, res, spacing, inc * os_scale);
+            }
+        }
+
+        // a band joining in starts from silence, one left out costs nothing
+        let band2 = self.params.band2.value();
+        let (band2_w_c, band2_res, band2_gain, band2_mode) = self.params.band2_values();
+        let band2_output = Filter::get_output_function(band2_mode.filter_mode());
+        let band2_scale = Simd::splat(band2_mode.output_scale(band2_gain));
+        if band2 {
+            let band2_filter_mode = band2_mode.filter_mode();
+            let w_c = Simd::splat(band2_w_c);
+            let k = Simd::splat(res_to_damping(band2_res));
+            let gain = Simd::splat(band2_gain);
+
+            if !self.band2_active {
+                self.band2.reset();
+            }
+            if raw || !self.band2_active {
+                Filter::get_update_function(band2_filter_mode)(&mut self.band2, w_c, k, gain);
+            } else {
+                Filter::get_smoothing_update_function(band2_filter_mode)(
+                    &mut self.band2,
+                    w_c,
+                    k,
+                    gain,
+                    inc,
+                );
+            }
+        }
+        self.band2_active = band2;
+
+        let filters = &mut self.filters[..num_stages];
+        let precise_filters = &mut self.precise_filters[..num_stages];
+
+        // runs `input` through every stage of the cascade
+        let run_cascade = |filters: &mut [BatchFilter],
+                           precise_filters: &mut [PreciseFilter],
+                           input: f32x2,
+                           tick: bool| {
+            if double_precision {
+                return precise_filters.iter_mut().fold(input, |sample, f| {
+                    if tick {
+                        f.tick();
+                    }
+                    f.process(sample)
+                });
+            }
+
+            filters.iter_mut().fold(input, |sample, f| {
+                if tick {
+                    f.tick();
+                }
+                f.process(sample)
+            })
+        };
+
+        let blend_len = self.params.blend_smoothing.value() / 1000. * TAU / self.two_pi_tick;
+        for (ramp, param) in [
+            (&mut self.input_trim_ramp, &self.params.band1_input_gain),
+            (&mut self.trim_ramp, &self.params.band1_gain),
+            (&mut self.band2_input_ramp, &self.params.band2_input_gain),
+            (&mut self.band2_trim_ramp, &self.params.band2_gain),
+            (&mut self.width_ramp, &self.params.width),
+        ] {
+            if raw {
+                ramp.reset(param.value());
+            } else {
+                ramp.set_target(param.value(), blend_len);
+            }
+        }
+
+        let bypass = if self.params.bypass.value() { 1. } else { 0. };
+        self.bypass_ramp.set_target(bypass, self.smoothing_len);
+
+        let dry_main = self.params.main_output.value() == MainOutput::Dry && !send_mode;
+        let match_dry = dry_main && self.params.match_dry.value();
+        let rms_coeff = Follower::coefficient(LEVEL_MATCH_MS / 1000., self.two_pi_tick);
+        let max_match = util::db_to_gain(MAX_LEVEL_MATCH_DB);
+        let mut aux_frames = aux
+            .outputs
+            .first_mut()
+            .map(|aux| aux.iter_samples().skip(block.start));
+        let mut input_correlation = Correlation::default();
+        let mut output_correlation = Correlation::default();
+
+        // the cascade runs a chunk at a time, between the stages before and after it, so that
+        // each stage can filter the whole chunk at once while its coefficients are static
+        let mut chunk_start = block.start;
+        while chunk_start < block.end {
+            let chunk_len = CHUNK_LEN.min(block.end - chunk_start);
+            let mut dry_frames = [Simd::splat(0.); CHUNK_LEN];
+            // the cascade's input, then its output, at its rate
+            let mut stream = [Simd::splat(0.); CHUNK_LEN * MAX_RATIO];
+            let mut cv_coefficients = [None; CHUNK_LEN];
+            let mut ping_frame = None;
+
+            for (i, mut input_frame) in buffer
+                .iter_samples()
+                .skip(chunk_start)
+                .take(chunk_len)
+                .enumerate()
+            {
+                let mut frame_iter = input_frame.iter_mut();
+                #[cfg(not(feature = "safe"))]
+                // SAFETY: we checked above that we have at least one sample per frame
+                let l = unsafe { frame_iter.next().unwrap_unchecked() };
+                #[cfg(feature = "safe")]
+                let l = frame_iter.next().unwrap();
+                let r = frame_iter.next();
+
+                let mut frame = Simd::from_array([*l, r.as_deref().copied().unwrap_or(*l)]);
+
+                // a single NaN or Inf would permanently poison the filters' states
+                let finite = frame.is_finite();
+                if !finite.all() {
+                    frame = finite.select(frame, Simd::splat(0.));
+                    self.params.non_finite_input.store(true, Ordering::Relaxed);
+                }
+
+                // unlike the ping, the burst goes through the whole chain, as if it was input
+                if let Some(burst) = self.audition.next() {
+                    frame += burst;
+                }
+
+                let dry = frame;
+                dry_frames[i] = dry;
+
+                if !self.offline {
+                    let [l_sample, r_sample] = dry.to_array();
+                    self.params.input_spectrum.push((l_sample + r_sample) * 0.5);
+                }
+
+                if drive_enabled {
+                    let drive = self.params.drive.smoothed.next();
+                    frame = drive_character.process(frame, drive, headroom);
+                }
+
+                // the noise would only keep the gate's output from ever settling
+                if anti_denormal && !self.silence.is_closed(silence_hold) {
+                    frame += self.noise.next_f32x2() * Simd::splat(ANTI_DENORMAL_NOISE);
+                }
+
+                if freeze {
+                    frame = Simd::splat(0.);
+                }
+
+                if let Some(&cv) = cv_samples.as_mut().and_then(Iterator::next) {
+                    let w_cv = (w_c * Simd::splat(f32::exp2(cv * cv_depth)))
+                        .simd_clamp(Simd::splat(w_min), Simd::splat(w_max));
+                    let (w_cv, res_cv) = flavor.apply(w_cv, res);
+                    let w_cv = (w_cv * os_scale).to_array().map(|w| prewarp.warp(w));
+                    cv_coefficients[i] = Some((Simd::from_array(w_cv), res_cv));
+                }
+
+                if mid_side_mode {
+                    frame = mid_side(frame) * Simd::splat(0.5);
+                }
+
+                frame *= Simd::splat(self.input_trim_ramp.next());
+
+                // the SVFs' states can't be written to, so the ping is fed straight into the
+                // cascade's input, past the drive, the mid/side matrix and the oversampler
+                if ping {
+                    ping_frame = Some(i * os_ratio);
+                    ping = false;
+                }
+
+                let os_frames = &mut stream[i * os_ratio..][..os_ratio];
+                if os_ratio == 1 {
+                    os_frames[0] = frame;
+                } else {
+                    let mut upsampled = [Simd::splat(0.); MAX_RATIO];
+                    self.oversampler.upsample(os_factor, frame, &mut upsampled);
+                    os_frames.copy_from_slice(&upsampled[..os_ratio]);
+                }
+            }
+
+            let stream = &mut stream[..chunk_len * os_ratio];
+
+            // the bank runs on the cascade's input, without the ping
+            let mut bank_frames = [Simd::splat(0.); CHUNK_LEN * MAX_RATIO];
+            if bank_bands > 0 {
+                for (output, &input) in bank_frames.iter_mut().zip(stream.iter()) {
+                    *output = self.filter_bank.process(input, bank_bands);
+                }
+            }
+
+            if let Some(n) = ping_frame {
+                stream[n] += Simd::splat(PING_LEVEL);
+            }
+
+            // one frame at a time while the coefficients move, else a stage at a time
+            if double_precision || ticks_left > 0 || cv_samples.is_some() {
+                for (n, sample) in stream.iter_mut().enumerate() {
+                    if let Some((w_cv, res_cv)) = cv_coefficients[n / os_ratio].take() {
+                        for f in filters.iter_mut() {
+                            f.update(prototypes(res_cv), w_cv);
+                        }
+                        for f in precise_filters.iter_mut() {
+                            f.update(prototypes(res_cv), w_cv);
+                        }
+                    }
+
+                    let tick = ticks_left > 0;
+                    if tick {
+                        ticks_left -= 1;
+                    }
+                    *sample = run_cascade(filters, precise_filters, *sample, tick);
+                }
+            } else {
+                for f in filters.iter_mut() {
+                    f.process_block(stream);
+                }
+            }
+
+            if bank_bands > 0 {
+                for (output, bank) in stream.iter_mut().zip(bank_frames) {
+                    *output += bank;
+                }
+            }
+
+            for (i, mut outupt_frame) in buffer
+                .iter_samples()
+                .skip(chunk_start)
+                .take(chunk_len)
+                .enumerate()
+            {
+                let mut frame_iter = outupt_frame.iter_mut();
+                #[cfg(not(feature = "safe"))]
+                // SAFETY: we checked above that we have at least one sample per frame
+                let l = unsafe { frame_iter.next().unwrap_unchecked() };
+                #[cfg(feature = "safe")]
+                let l = frame_iter.next().unwrap();
+                let r = frame_iter.next();
+
+                let dry = dry_frames[i];
+                let os_frames = &stream[i * os_ratio..][..os_ratio];
+                let mut frame = if os_ratio == 1 {
+                    os_frames[0]
+                } else {
+                    let mut downsampled = [Simd::splat(0.); MAX_RATIO];
+                    downsampled[..os_ratio].copy_from_slice(os_frames);
+                    self.oversampler.downsample(os_factor, &mut downsampled)
+                };
+
+                // both ramps keep moving while the band's off, like the filter's own
+                let band2_input = self.band2_input_ramp.next();
+                let band2_trim = self.band2_trim_ramp.next();
+                if band2 {
+                    self.band2.update_all_smoothers();
+                    self.band2.process(frame * Simd::splat(band2_input));
+                    frame = band2_output(&self.band2) * band2_scale * Simd::splat(band2_trim);
+                }
+
+                if mid_side_mode {
+                    frame = mid_side(frame);
+                }
+
+                frame *= Simd::splat(self.trim_ramp.next()) * output_scale;
+
+                if phase_eq {
+                    frame = self.phase_eq.process(frame);
+                }
+
+                if num_phaser_stages > 0 {
+                    frame = self
+                        .phaser
+                        .process(frame, num_phaser_stages, phaser_feedback);
+                }
+
+                let width = self.width_ramp.next();
+                if width != 1. && !dual_mono {
+                    frame = apply_width(frame, width);
+                }
+
+                if aa {
+                    frame = self.aa_filter.process(frame);
+                }
+
+                let mode_fade = self.mode_fade.next();
+                if mode_fade < 1. {
+                    frame *= Simd::splat(mode_fade);
+                }
+
+                if null_test {
+                    frame -= dry;
+                }
+
+                frame = monitor.apply(frame);
+
+                if self.fade_in_remaining > 0 {
+                    let fade = 1. - self.fade_in_remaining as f32 / self.fade_in_len as f32;
+                    frame *= Simd::splat(fade);
+                    self.fade_in_remaining -= 1;
+                }
+
+                // once everything's been silent for long enough, clear every stage's state and
+                // output exact zeros, until the input comes back. The stages up to the cascade
+                // have already run through the chunk, and are cleared as of its end
+                if self.silence.process(dry, frame, silence_hold) {
+                    filters.iter_mut().for_each(BatchFilter::reset);
+                    precise_filters.iter_mut().for_each(PreciseFilter::reset);
+                    self.band2.reset();
+                    self.phase_eq.reset();
+                    self.phaser.reset();
+                    self.filter_bank.reset();
+                    self.aa_filter.reset();
+                    self.oversampler.reset();
+                }
+                if self.silence.is_closed(silence_hold) {
+                    frame = Simd::splat(0.);
+                }
+
+                // TPDF, the sum of two uniform distributions, spanning ±1 LSB
+                if dither {
+                    let tpdf = self.dither_rng.next_f32x2() + self.dither_rng.next_f32x2();
+                    frame += tpdf * Simd::splat(0.5 * DITHER_LSB);
+                }
+
+                let [l_sample, r_sample] = frame.to_array();
+
+                if !self.offline {
+                    self.params.spectrum.push((l_sample + r_sample) * 0.5);
+                    input_correlation.push(dry);
+                    output_correlation.push(frame);
+                }
+
+                if let Some(mut aux_frame) = aux_frames.as_mut().and_then(Iterator::next) {
+                    for (sample, wet) in aux_frame.iter_mut().zip([l_sample, r_sample]) {
+                        *sample = wet;
+                    }
+                }
+
+                let [l_out, r_out] = if dry_main {
+                    if match_dry {
+                        self.dry_rms
+                            .process((dry * dry).reduce_sum() * 0.5, rms_coeff);
+                        self.wet_rms
+                            .process((frame * frame).reduce_sum() * 0.5, rms_coeff);
+
+                        let ratio =
+                            (self.wet_rms.mean_square() / self.dry_rms.mean_square()).sqrt();
+                        let ratio = if ratio.is_finite() {
+                            ratio.clamp(max_match.recip(), max_match)
+                        } else {
+                            1.
+                        };
+                        (dry * Simd::splat(ratio)).to_array()
+                    } else {
+                        dry.to_array()
+                    }
+                } else {
+                    [l_sample, r_sample]
+                };
+
+                // the main output, wet or dry, is kept under the ceiling
+                let out = clipper.apply(Simd::from_array([l_out, r_out]), ceiling);
+
+                let bypass = self.bypass_ramp.next();
+                let [l_out, r_out] = if bypass > 0. {
+                    (out + (dry - out) * Simd::splat(bypass)).to_array()
+                } else {
+                    out.to_array()
+                };
+                *l = l_out;
+                if let Some(r) = r {
+                    *r = r_out;
+                }
+            }
+
+            chunk_start += chunk_len;
+        }
+
+        for (correlation, shared) in [
+            (input_correlation, &self.params.input_correlation),
+            (output_correlation, &self.params.output_correlation),
+        ] {
+            if let Some(value) = correlation.value() {
+                shared.store(value, Ordering::Relaxed);
+            }
+        }
     }
 }
 
@@ -109,24 +2996,57 @@ impl Plugin for SVFFilter {
 
     const EMAIL: &'static str = "monke@monkey.com";
 
-    const VERSION: &'static str = "0.0.1";
+    const VERSION: &'static str = "0.0.2";
 
-    const MIDI_INPUT: MidiConfig = MidiConfig::None;
+    // CCs and channel pressure, on top of notes, for `midi_mod_source`
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
 
-    const MIDI_OUTPUT: MidiConfig = MidiConfig::None;
+    const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
 
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
     const HARD_REALTIME_ONLY: bool = false;
 
-    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
-        main_input_channels: NonZeroU32::new(NUM_CHANNELS as u32),
-        main_output_channels: NonZeroU32::new(NUM_CHANNELS as u32),
-        ..AudioIOLayout::const_default()
-    }];
+    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(NUM_CHANNELS as u32),
+            main_output_channels: NonZeroU32::new(NUM_CHANNELS as u32),
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(1),
+            main_output_channels: NonZeroU32::new(1),
+            ..AudioIOLayout::const_default()
+        },
+        // a mono control signal on the aux input modulates the cutoff, at audio rate
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(NUM_CHANNELS as u32),
+            main_output_channels: NonZeroU32::new(NUM_CHANNELS as u32),
+            aux_input_ports: &[new_nonzero_u32(1)],
+            names: PortNames {
+                aux_inputs: &["Cutoff CV"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+        // the wet signal is also sent to the aux output, for blending externally
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(NUM_CHANNELS as u32),
+            main_output_channels: NonZeroU32::new(NUM_CHANNELS as u32),
+            aux_output_ports: &[new_nonzero_u32(NUM_CHANNELS as u32)],
+            names: PortNames {
+                aux_outputs: &["Wet"],
+                ..PortNames::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        },
+    ];
 
     type SysExMessage = ();
 
+    #[cfg(feature = "gui")]
+    type BackgroundTask = matching::Task;
+    #[cfg(not(feature = "gui"))]
     type BackgroundTask = ();
 
     fn params(&self) -> Arc<dyn Params> {
@@ -136,80 +3056,152 @@ impl Plugin for SVFFilter {
     fn process(
         &mut self,
         buffer: &mut Buffer,
-        _aux: &mut AuxiliaryBuffers,
-        _context: &mut impl ProcessContext<Self>,
+        aux: &mut AuxiliaryBuffers,
+        context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        let (w_c, res, gain, mode) = self.get_param_values();
-        let update = Filter::get_smoothing_update_function(mode);
-        let get_output = Filter::get_output_function(mode);
-
-        let f = &mut self.filter;
+        // don't trust the host on the layout: a single channel runs through both lanes,
+        // and any extra channels are passed through as is
+        if buffer.channels() == 0 {
+            return ProcessStatus::Normal;
+        }
 
-        update(
-            f,
-            w_c,
-            res,
-            gain,
-            Simd::splat(self.smoothing_inc.min(1. / buffer.samples() as f32)),
-        );
+        self.follow_sample_rate(context.transport().sample_rate);
 
-        for mut outupt_frame in buffer.iter_samples() {
-            f.update_all_smoothers();
+        let playing = context.transport().playing;
+        if self.was_playing && !playing && self.params.clear_on_stop.value() {
+            self.reset();
+        }
+        self.was_playing = playing;
 
-            let mut frame_iter = outupt_frame.iter_mut();
-            // SAFETY: we only support a stereo configuration (so we have exactly two samples per frame)
-            let l = unsafe { frame_iter.next().unwrap_unchecked() };
-            let r = unsafe { frame_iter.next().unwrap_unchecked() };
+        let glide_samples =
+            self.params.glide_time.unmodulated_plain_value() * TAU / (1000. * self.two_pi_tick);
 
-            let mut frame = Simd::from_array([*l, *r]);
-            f.process(frame);
+        // the buffer is split at note events, so that the envelope's gate is sample-accurate,
+        // and, while the LFO runs, every `LFO_BLOCK_LEN` samples, so that the cutoff follows
+        // its shape. Other events are applied at the start of the split they fall in
+        let num_samples = buffer.samples();
+        let max_block_len = if self.params.lfo_depth.unmodulated_plain_value() != 0. {
+            LFO_BLOCK_LEN
+        } else {
+            num_samples
+        };
+        let mut next_event = context.next_event();
+        let mut block_start = 0;
+        loop {
+            let mut block_end = num_samples.min(block_start + max_block_len);
+            while let Some(event) = next_event.take() {
+                // events past the end (from misbehaving hosts) land on the last sample
+                let timing = (event.timing() as usize).min(num_samples.saturating_sub(1));
+                let is_note = matches!(event, NoteEvent::NoteOn { .. } | NoteEvent::NoteOff { .. });
+                let past_split = timing >= block_end && block_end < num_samples;
+                if (is_note && timing > block_start) || past_split {
+                    block_end = block_end.min(timing);
+                    next_event = Some(event);
+                    break;
+                }
+                self.handle_event(event, glide_samples, context);
+                next_event = context.next_event();
+            }
 
-            frame = get_output(f);
+            self.process_block(buffer, aux, context, block_start..block_end);
 
-            let [l_sample, r_sample] = frame.to_array();
+            if block_end >= num_samples {
+                break;
+            }
+            block_start = block_end;
+        }
 
-            *l = l_sample;
-            *r = r_sample;
+        if !self.offline {
+            self.params.block_size.store(num_samples, Ordering::Relaxed);
         }
 
         ProcessStatus::Normal
     }
 
-    fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
+    #[cfg(feature = "gui")]
+    fn editor(&mut self, async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
         let params = self.params.clone();
         create_vizia_editor(
-            self.params.vizia_state.clone(),
+            self.params.editor.vizia_state.clone(),
             ViziaTheming::Builtin,
-            move |cx, _gui_ctx| {
-                SVFBode::new(cx, params.clone());
+            move |cx, gui_ctx| {
+                editor::create(cx, params.clone(), async_executor.clone(), gui_ctx);
             },
         )
     }
 
+    // the cutoffs of older states, saved over a narrower range, stay where they were, in Hz
+    fn filter_state(state: &mut PluginState) {
+        if state.version != LEGACY_CUTOFF_VERSION {
+            return;
+        }
+
+        let (legacy, range) = (CutoffRange::LEGACY, CutoffRange::default());
+        for id in CUTOFF_PARAM_IDS {
+            if let Some(ParamValue::F32(value)) = state.params.get_mut(id) {
+                *value = range.normalized(legacy.cutoff_hz(*value, legacy.max_hz));
+            }
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        Box::new(matching::execute)
+    }
+
     fn initialize(
         &mut self,
         _audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
-        _context: &mut impl InitContext<Self>,
+        context: &mut impl InitContext<Self>,
     ) -> bool {
-        let sr = buffer_config.sample_rate;
-        self.two_pi_tick = TAU / sr;
+        // layout changes always re-initialize, then reset, the plugin, clearing all state
+        self.set_sample_rate(buffer_config.sample_rate);
+        self.offline = matches!(buffer_config.process_mode, ProcessMode::Offline);
 
-        self.smoothing_inc = 50. / sr;
+        // oversampling is the only source of latency, the filters being minimum phase
+        self.oversampling_config = None;
+        if let Some(latency) = self.configure_oversampling() {
+            context.set_latency_samples(latency);
+        }
 
-        let (w_c, res, gain, mode) = self.get_param_values();
-        let update = Filter::get_update_function(mode);
+        self.prime_filters();
+        // the host may still restore state between now and the first process call
+        self.needs_priming = true;
 
-        update(&mut self.filter, w_c, res, gain);
+        self.params.state_generation.fetch_add(1, Ordering::Relaxed);
 
-        self.params
-            .two_pi_tick
-            .store(self.two_pi_tick, Ordering::Relaxed);
         true
     }
 
     fn reset(&mut self) {
-        self.filter.reset();
+        self.filters.iter_mut().for_each(BatchFilter::reset);
+        self.precise_filters
+            .iter_mut()
+            .for_each(PreciseFilter::reset);
+        self.band2.reset();
+        self.frozen = None;
+        self.envelope.reset();
+        self.envelope_level = 0.;
+        self.followers.iter_mut().for_each(Follower::reset);
+        self.cutoff_poly_mod.reset();
+        self.res_poly_mod.reset();
+        self.key_tracker.reset();
+        self.key_octaves = 0.;
+        self.midi_mod = 0.;
+        self.lfo.reset();
+        self.lfo_octaves = 0.;
+        self.phaser.reset();
+        self.phase_eq.reset();
+        self.filter_bank.reset();
+        self.aa_filter.reset();
+        self.oversampler.reset();
+        self.seed_rngs();
+        self.audition.reset();
+        self.silence.reset();
+        self.dry_rms.reset();
+        self.wet_rms.reset();
+        self.needs_priming = true;
     }
 }
 
@@ -230,7 +3222,413 @@ impl ClapPlugin for SVFFilter {
     const CLAP_SUPPORT_URL: Option<&'static str> = None;
 
     const CLAP_FEATURES: &'static [ClapFeature] = &[ClapFeature::AudioEffect, ClapFeature::Filter];
+
+    // the filter is monophonic, poly modulation of every voice gets summed
+    const CLAP_POLY_MODULATION_CONFIG: Option<PolyModulationConfig> = Some(PolyModulationConfig {
+        max_voice_capacity: MAX_VOICES as u32,
+        supports_overlapping_voices: true,
+    });
 }
 
 nih_export_clap!(SVFFilter);
 nih_export_vst3!(SVFFilter);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The plugin, set up as a host would, at `sample_rate`, with its default parameters.
+    fn primed(sample_rate: f32) -> SVFFilter {
+        let mut plugin = SVFFilter::default();
+        plugin.set_sample_rate(sample_rate);
+        plugin.prime_filters();
+        plugin
+    }
+
+    /// The first stage of the cascade, jumped straight to the plugin's current settings.
+    fn reference_stage(plugin: &SVFFilter) -> BatchFilter {
+        let (w_c, res, gain, _) = plugin.get_param_values();
+        let os_ratio = plugin.params.quality_settings().oversampling.ratio();
+        let os_scale = Simd::splat(1. / os_ratio as f32);
+        let (w_c, res) = plugin.filter_coefficients(w_c, res, os_scale);
+        let stage_gain = plugin.params.slope.value().stage_gain(gain);
+        let band_split = plugin.params.band_split.value();
+
+        let mut filter = BatchFilter::default();
+        filter.update(
+            precise_prototypes(plugin.mode_fade.active(), band_split, res, stage_gain),
+            w_c,
+        );
+        filter
+    }
+
+    /// `filter`'s impulse response, ticking its coefficients every sample, as `process` does.
+    fn impulse_response(filter: &mut BatchFilter, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|n| {
+                filter.tick();
+                filter.process(Simd::splat(if n == 0 { 1. } else { 0. }))[0]
+            })
+            .collect()
+    }
+
+    /// Runs noise through a stage made by `new_stage`, twice in a different one in each lane,
+    /// then once each on its own. The lanes must match their own runs bit for bit, and a
+    /// silent lane stay exactly silent.
+    fn assert_lanes_independent<S: FnMut(f32x2) -> f32x2>(name: &str, new_stage: impl Fn() -> S) {
+        let run = |left: &[f32], right: &[f32]| {
+            let mut stage = new_stage();
+            left.iter()
+                .zip(right)
+                .map(|(&l, &r)| stage(Simd::from_array([l, r])))
+                .collect::<Vec<_>>()
+        };
+        let lane = |frames: &[f32x2], i: usize| frames.iter().map(|f| f[i]).collect::<Vec<_>>();
+
+        let mut rng = Rng::new(7);
+        let left: Vec<f32> = (0..2048).map(|_| rng.next_f32()).collect();
+        let right: Vec<f32> = (0..2048).map(|_| rng.next_f32()).collect();
+        let silence = vec![0.; left.len()];
+
+        let stereo = run(&left, &right);
+        assert_eq!(
+            lane(&stereo, 0),
+            lane(&run(&left, &left), 0),
+            "{name}: left"
+        );
+        assert_eq!(
+            lane(&stereo, 1),
+            lane(&run(&right, &right), 0),
+            "{name}: right"
+        );
+
+        let one_sided = run(&left, &silence);
+        assert_eq!(lane(&one_sided, 1), silence, "{name}: silent lane");
+    }
+
+    #[test]
+    fn stereo_stages_keep_their_lanes_apart() {
+        assert_lanes_independent("cascade", || {
+            let mut plugin = primed(44100.);
+            move |x: f32x2| {
+                let filter = &mut plugin.filters[0];
+                filter.tick();
+                filter.process(x)
+            }
+        });
+
+        assert_lanes_independent("precise cascade", || {
+            let mut plugin = primed(44100.);
+            move |x: f32x2| plugin.precise_filters[0].process(x)
+        });
+
+        let band2_mode = primed(44100.).params.band2_values().3.filter_mode();
+        let get_band2_output = Filter::get_output_function(band2_mode);
+        assert_lanes_independent("band 2", || {
+            let mut plugin = primed(44100.);
+            move |x: f32x2| {
+                plugin.band2.process(x);
+                get_band2_output(&plugin.band2)
+            }
+        });
+
+        assert_lanes_independent("phaser", || {
+            let mut plugin = primed(44100.);
+            move |x: f32x2| plugin.phaser.process(x, MAX_STAGES, 0.5)
+        });
+
+        assert_lanes_independent("phase eq", || {
+            let mut plugin = primed(44100.);
+            move |x: f32x2| plugin.phase_eq.process(x)
+        });
+
+        assert_lanes_independent("filter bank", || {
+            let mut plugin = primed(44100.);
+            move |x: f32x2| plugin.filter_bank.process(x, MAX_BANDS)
+        });
+
+        assert_lanes_independent("oversampler", || {
+            let mut oversampler = Oversampler::default();
+            oversampler.set_quality(OversamplingQuality::High);
+            move |x: f32x2| {
+                let mut frames = [Simd::splat(0.); MAX_RATIO];
+                oversampler.upsample(OversamplingFactor::X4, x, &mut frames);
+                oversampler.downsample(OversamplingFactor::X4, &mut frames)
+            }
+        });
+    }
+
+    #[test]
+    fn first_block_starts_at_the_targets() {
+        for sample_rate in [44100., 96000.] {
+            let mut plugin = primed(sample_rate);
+            let mut reference = reference_stage(&plugin);
+
+            // any leftover ramp would show up against a filter that never ramped
+            assert_eq!(
+                impulse_response(&mut plugin.filters[0], 512),
+                impulse_response(&mut reference, 512),
+                "{sample_rate} Hz"
+            );
+        }
+    }
+
+    #[test]
+    fn reset_reprimes_from_the_current_parameters() {
+        let mut plugin = primed(44100.);
+
+        // leave the cascade mid-ramp towards other settings, as a previous session would
+        for filter in &mut plugin.filters {
+            filter.smoothing_update(
+                [biquad::analog_prototype(Mode::HighPass, 0.1, 1.); 2],
+                Simd::splat(0.5),
+                Simd::splat(1. / 1024.),
+            );
+            (0..100).for_each(|_| filter.tick());
+        }
+
+        plugin.reset();
+        assert!(plugin.needs_priming);
+        plugin.prime_if_needed();
+        assert!(!plugin.needs_priming);
+
+        let expected = impulse_response(&mut reference_stage(&plugin), 512);
+        for (i, filter) in plugin.filters.iter_mut().enumerate() {
+            assert_eq!(impulse_response(filter, 512), expected, "stage {i}");
+        }
+    }
+
+    #[test]
+    fn sample_rate_changes_keep_the_cutoff() {
+        let mut plugin = primed(44100.);
+        let (w_c, ..) = plugin.get_param_values();
+        let cutoff_hz = w_c / Simd::splat(plugin.two_pi_tick);
+        assert_eq!(plugin.max_freq, NYQUIST_MARGIN * 44100.);
+
+        plugin.follow_sample_rate(96000.);
+        assert!(plugin.needs_priming);
+        assert_eq!(plugin.two_pi_tick, TAU / 96000.);
+        assert_eq!(
+            plugin.params.two_pi_tick.load(Ordering::Relaxed),
+            TAU / 96000.
+        );
+        let max_hz = plugin.params.cutoff_range.max_hz;
+        assert_eq!(plugin.max_freq, max_hz);
+        assert_eq!(plugin.params.max_freq.load(Ordering::Relaxed), max_hz);
+        assert_eq!(plugin.smoothing_len, 1920.);
+
+        plugin.prime_if_needed();
+        let (w_c, ..) = plugin.get_param_values();
+        let moved = (w_c / Simd::splat(plugin.two_pi_tick) / cutoff_hz - Simd::splat(1.)).abs();
+        assert!(moved.simd_lt(Simd::splat(1e-4)).all(), "{moved:?}");
+
+        // the same rate again changes nothing
+        plugin.follow_sample_rate(96000.);
+        assert!(!plugin.needs_priming);
+    }
+
+    #[test]
+    fn res_guard_bounds_the_lowest_cutoff() {
+        let two_pi_tick = TAU / BASE_SAMPLE_RATE;
+        let w_guard = two_pi_tick * RES_GUARD_FREQ;
+        let w_min = two_pi_tick * CutoffRange::default().min_hz;
+
+        // untouched an octave above the guard
+        let floor = res_guard_floor(2. * w_guard, w_guard, 1.);
+        assert_eq!(floor, res_to_damping(MIN_RES));
+
+        let res = damping_to_res(res_guard_floor(w_min, w_guard, 1.));
+        assert!(res > 2.5 * MIN_RES, "{res}");
+
+        // driven right at the resonance, long enough to settle
+        let input: Vec<f32> = (0..(40. / (res * w_min)) as usize)
+            .map(|n| f32::sin(w_min * n as f32))
+            .collect();
+        let output = sweep::render(FilterMode::LP, w_min, res, 1., &input);
+        let peak = output.iter().fold(0f32, |peak, x| peak.max(x.abs()));
+
+        assert!(peak.is_finite());
+        assert!(peak <= 1.01 * Mode::LowPass.resonance_peak(res), "{peak}");
+        assert!(peak > 0.9 * Mode::LowPass.resonance_peak(res), "{peak}");
+    }
+
+    #[test]
+    fn lowpass_passes_dc_and_cuts_nyquist() {
+        let analog_cutoff = f32::tan(PI * 1000. / BASE_SAMPLE_RATE);
+
+        for num_stages in 1..=MAX_CASCADE_STAGES {
+            let dc = transfer_function(FilterMode::LP, num_stages, 0., analog_cutoff, 0.5, 1.);
+            assert!((dc.norm() - 1.).abs() < 1e-6, "{num_stages}: {dc}");
+            let w = 0.999 * PI;
+            let top = transfer_function(FilterMode::LP, num_stages, w, analog_cutoff, 0.5, 1.);
+            assert!(top.norm() < 1e-3, "{num_stages}: {top}");
+        }
+    }
+
+    #[test]
+    fn transfer_function_matches_the_rendered_impulse_response() {
+        let w_c = TAU * 1000. / BASE_SAMPLE_RATE;
+        let mut impulse = vec![0.; 4096];
+        impulse[0] = 1.;
+
+        for mode in (0..Mode::variants().len()).map(Mode::from_index) {
+            let h = sweep::render(mode.filter_mode(), w_c, 0.5, 2., &impulse);
+
+            for w in [0.01, 0.1, 0.5, 1., 2., 3.] {
+                let dft = h
+                    .iter()
+                    .enumerate()
+                    .fold(Complex::new(0., 0.), |sum, (n, &h)| {
+                        sum + Complex::from_polar(h, -w * n as f32)
+                    });
+                let expected =
+                    transfer_function(mode.filter_mode(), 1, w, f32::tan(w_c * 0.5), 0.5, 2.);
+                assert!(
+                    (dft - expected).norm() < 1e-3,
+                    "{mode:?} at {w}: {dft} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn plot_matches_the_sound() {
+        let mut plugin = primed(44100.);
+        let (w_c, ..) = plugin.effective_coefficients();
+        let params = plugin.params.clone();
+        let os_ratio = params.quality_settings().oversampling.ratio() as f32;
+        let cutoff_hz = w_c[0] / plugin.two_pi_tick;
+        // a single stage, at the default slope
+        assert_eq!(params.slope.value().num_stages(), 1);
+
+        for freq_hz in [0.5 * cutoff_hz, cutoff_hz, 2. * cutoff_hz] {
+            // the plot, bar the second band and the output's scaling
+            let active_mode = params.mode.modulated_plain_value();
+            let (_, res, gain) = plugin.effective_coefficients();
+            let plotted = plugin.frequency_response(freq_hz).norm()
+                / params.band2_response(freq_hz * plugin.two_pi_tick).norm()
+                / active_mode.output_scale(gain[0])
+                / params.res_compensation(active_mode, damping_to_res(res[0]), 1);
+
+            // a tone through it, at its (oversampled) rate
+            let w = freq_hz * plugin.two_pi_tick / os_ratio;
+            let len = (200. * TAU / w) as usize;
+            let filter = &mut plugin.filters[0];
+            filter.reset();
+            let (mut input, mut output) = (0f64, 0f64);
+            for n in 0..2 * len {
+                let x = f64::sin(f64::from(w) * n as f64) as f32;
+                filter.tick();
+                let y = filter.process(Simd::splat(x))[0];
+                if n >= len {
+                    input += f64::from(x * x);
+                    output += f64::from(y * y);
+                }
+            }
+            let measured = (output / input).sqrt() as f32;
+
+            let error_db = 20. * (measured / plotted).log10();
+            assert!(error_db.abs() < 0.1, "{freq_hz} Hz: {error_db} dB");
+        }
+    }
+
+    #[test]
+    fn chunks_match_the_per_frame_cascade() {
+        let mut rng = Rng::new(3);
+        let input: Vec<f32x2> = (0..1000).map(|_| rng.next_f32x2()).collect();
+        let [mut per_frame, mut chunked] = [(); 2].map(|_| primed(44100.));
+
+        let expected: Vec<_> = input
+            .iter()
+            .map(|&x| per_frame.filters.iter_mut().fold(x, |x, f| f.process(x)))
+            .collect();
+
+        // the static path of `process_block`, each stage filtering the whole chunk
+        let mut frames = input;
+        for chunk in frames.chunks_mut(CHUNK_LEN) {
+            for filter in &mut chunked.filters {
+                filter.process_block(chunk);
+            }
+        }
+
+        for (n, (frame, expected)) in frames.iter().zip(expected).enumerate() {
+            let error = (*frame - expected).abs().reduce_max();
+            assert!(error < 1e-5, "[{n}]: {frame:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn the_cutoff_shows_where_the_filter_stops() {
+        let plugin = primed(44100.);
+        let max_freq = NYQUIST_MARGIN * 44100.;
+
+        let top = plugin.params.cutoff.normalized_value_to_string(1., true);
+        assert_eq!(top, format!("{:.2} kHz", max_freq / 1000.));
+
+        let range = plugin.params.cutoff_range;
+        assert_eq!(range.cutoff_hz(0., max_freq), range.min_hz);
+        assert_eq!(range.cutoff_hz(1., max_freq), max_freq);
+        assert!((range.normalized(range.cutoff_hz(0.3, max_freq)) - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn legacy_states_keep_their_cutoffs() {
+        let legacy = CutoffRange::LEGACY;
+        let saved = |version: &str| PluginState {
+            version: version.to_owned(),
+            params: CUTOFF_PARAM_IDS
+                .iter()
+                .map(|&id| (id.to_owned(), ParamValue::F32(0.5)))
+                .chain([("res".to_owned(), ParamValue::F32(0.5))])
+                .collect(),
+            fields: Default::default(),
+        };
+        let value = |state: &PluginState, id: &str| match state.params[id] {
+            ParamValue::F32(value) => value,
+            _ => unreachable!(),
+        };
+
+        let mut state = saved(LEGACY_CUTOFF_VERSION);
+        SVFFilter::filter_state(&mut state);
+        for id in CUTOFF_PARAM_IDS {
+            let hz = CutoffRange::default().cutoff_hz(value(&state, id), f32::INFINITY);
+            let legacy_hz = legacy.cutoff_hz(0.5, legacy.max_hz);
+            assert!((hz / legacy_hz - 1.).abs() < 1e-4, "{id}: {hz} Hz");
+        }
+        assert_eq!(value(&state, "res"), 0.5);
+
+        // current states are left alone
+        let mut state = saved(SVFFilter::VERSION);
+        SVFFilter::filter_state(&mut state);
+        assert!(CUTOFF_PARAM_IDS.iter().all(|id| value(&state, id) == 0.5));
+    }
+
+    #[test]
+    fn quality_presets_set_what_they_document() {
+        assert!(Quality::Custom.settings().is_none());
+
+        for (quality, ratio, taps, refined) in [
+            (Quality::Eco, 1, 15, false),
+            (Quality::Normal, 2, 31, false),
+            (Quality::High, 4, 31, true),
+            (Quality::Ultra, 4, 63, true),
+        ] {
+            let settings = quality.settings().unwrap();
+            assert_eq!(settings.oversampling.ratio(), ratio, "{quality:?}");
+            assert_eq!(settings.os_quality.num_taps(), taps, "{quality:?}");
+            assert_eq!(settings.cascade_smoothing, refined, "{quality:?}");
+            assert_eq!(settings.high_res_plot, refined, "{quality:?}");
+        }
+    }
+
+    #[test]
+    fn smoothing_len_follows_the_sample_rate() {
+        let mut plugin = SVFFilter::default();
+
+        for sample_rate in [44100., 48000., 96000., 192000.] {
+            plugin.set_sample_rate(sample_rate);
+            let len = (SMOOTHING_MS / 1000. * sample_rate).round();
+            assert_eq!(plugin.smoothing_len, len, "{sample_rate} Hz");
+        }
+    }
+}
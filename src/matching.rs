@@ -0,0 +1,331 @@
+use core::sync::atomic::Ordering;
+
+use nih_plug::prelude::{nih_error, nih_log, AsyncExecutor, GuiContext, Param, ParamSetter};
+
+use crate::{
+    export, mode::Mode, transfer_function, Arc, Prewarp, SVFFilter, SVFParams, MAX_RES, MIN_RES,
+};
+
+const MAX_GAIN_DB: f32 = 30.;
+
+pub enum Task {
+    /// Fits the current mode's cutoff, resonance and gain to the editor's target curve
+    MatchCurve {
+        params: Arc<SVFParams>,
+        executor: AsyncExecutor<SVFFilter>,
+        gui_context: Arc<dyn GuiContext>,
+    },
+    /// Applies a match's result, must run on the GUI thread
+    ApplyMatch {
+        params: Arc<SVFParams>,
+        fit: Fit,
+        gui_context: Arc<dyn GuiContext>,
+    },
+    /// Writes the editor's last drawn response to an SVG file
+    ExportPlot { params: Arc<SVFParams> },
+    /// Logs a change of the filter's (left lane's) settings, `pos_samples` being the
+    /// transport's position, if the host reports it
+    #[cfg(feature = "param-log")]
+    LogParams {
+        pos_samples: Option<i64>,
+        mode: Mode,
+        cutoff_hz: f32,
+        res: f32,
+        gain_db: f32,
+    },
+}
+
+/// Reads a target curve, one `<frequency (Hz)> <gain (dB)>` pair per line, `#` starting a comment.
+pub fn parse_curve(text: &str) -> Vec<(f32, f32)> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.split('#').next()?;
+            let mut fields = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .filter(|field| !field.is_empty())
+                .map(str::parse::<f32>);
+
+            let freq = fields.next()?.ok()?;
+            let gain_db = fields.next()?.ok()?;
+
+            (freq.is_finite() && gain_db.is_finite() && freq > 0.).then_some((freq, gain_db))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+pub struct Fit {
+    /// normalized, as the cutoff parameter
+    pub cutoff: f32,
+    /// editor-side, as expected by the transfer functions
+    pub res: f32,
+    pub gain_db: f32,
+}
+
+/// The filter's settings a fit is heard through, as taken from `params`.
+#[derive(Clone, Copy)]
+pub struct Setup<'a> {
+    pub params: &'a SVFParams,
+    pub mode: Mode,
+    pub prewarp: Prewarp,
+    pub num_stages: usize,
+    /// Highest cutoff, in Hz, the filter reaches
+    pub max_freq: f32,
+    /// The rate the filter runs at
+    pub two_pi_tick: f32,
+}
+
+impl<'a> Setup<'a> {
+    fn new(params: &'a SVFParams) -> Self {
+        Self {
+            params,
+            mode: params.mode.value(),
+            prewarp: params.prewarp.value(),
+            num_stages: params.slope.value().num_stages(),
+            max_freq: params.max_freq.load(Ordering::Relaxed),
+            two_pi_tick: params.two_pi_tick.load(Ordering::Relaxed)
+                / params.quality_settings().oversampling.ratio() as f32,
+        }
+    }
+}
+
+/// Mean squared error, in dB, between the response of `setup` and `target`, the whole
+/// cascade's, scaled as in the plot.
+fn error(setup: Setup, target: &[(f32, f32)], fit: Fit) -> f32 {
+    let Setup {
+        params,
+        mode,
+        prewarp,
+        num_stages,
+        max_freq,
+        two_pi_tick,
+    } = setup;
+    let gain = 10f32.powf(fit.gain_db / 20.);
+    let cutoff_hz = params.cutoff_range.cutoff_hz(fit.cutoff, max_freq);
+    let cutoff = prewarp.analog(cutoff_hz * two_pi_tick);
+    let output_scale = mode.output_scale(gain) * params.res_compensation(mode, fit.res, num_stages);
+
+    let sum: f32 = target
+        .iter()
+        .map(|&(freq, target_db)| {
+            let w = (freq * two_pi_tick).min(3.);
+            let response =
+                transfer_function(mode.filter_mode(), num_stages, w, cutoff, fit.res, gain)
+                    * output_scale;
+            let db = (10. * f32::log10(response.norm_sqr())).max(-120.);
+            (db - target_db).powi(2)
+        })
+        .sum();
+
+    sum / target.len() as f32
+}
+
+/// Grid search, refined by coordinate descent.
+pub fn fit(setup: Setup, target: &[(f32, f32)]) -> Option<Fit> {
+    if target.is_empty() {
+        return None;
+    }
+
+    let mode = setup.mode;
+    let res_at = |t: f32| MAX_RES * (MIN_RES / MAX_RES).powf(t);
+    let gain_steps = if mode.uses_gain() { 13 } else { 1 };
+
+    let mut best = Fit {
+        cutoff: 0.5,
+        res: MAX_RES,
+        gain_db: 0.,
+    };
+    let mut best_error = f32::INFINITY;
+
+    for i in 0..32 {
+        for j in 0..16 {
+            for k in 0..gain_steps {
+                let candidate = Fit {
+                    cutoff: i as f32 / 31.,
+                    res: res_at(j as f32 / 15.),
+                    gain_db: if gain_steps > 1 {
+                        MAX_GAIN_DB * (2. * k as f32 / (gain_steps - 1) as f32 - 1.)
+                    } else {
+                        0.
+                    },
+                };
+
+                let candidate_error = error(setup, target, candidate);
+                if candidate_error < best_error {
+                    best = candidate;
+                    best_error = candidate_error;
+                }
+            }
+        }
+    }
+
+    let (mut cutoff_step, mut res_step, mut gain_step) = (1. / 62., 0.05, MAX_GAIN_DB / 12.);
+
+    for _ in 0..40 {
+        let mut candidates = vec![
+            Fit {
+                cutoff: (best.cutoff + cutoff_step).min(1.),
+                ..best
+            },
+            Fit {
+                cutoff: (best.cutoff - cutoff_step).max(0.),
+                ..best
+            },
+            Fit {
+                res: (best.res + res_step).min(MAX_RES),
+                ..best
+            },
+            Fit {
+                res: (best.res - res_step).max(MIN_RES),
+                ..best
+            },
+        ];
+
+        if mode.uses_gain() {
+            candidates.extend([
+                Fit {
+                    gain_db: (best.gain_db + gain_step).min(MAX_GAIN_DB),
+                    ..best
+                },
+                Fit {
+                    gain_db: (best.gain_db - gain_step).max(-MAX_GAIN_DB),
+                    ..best
+                },
+            ]);
+        }
+
+        let mut improved = false;
+        for candidate in candidates {
+            let candidate_error = error(setup, target, candidate);
+            if candidate_error < best_error {
+                best = candidate;
+                best_error = candidate_error;
+                improved = true;
+            }
+        }
+
+        if !improved {
+            cutoff_step *= 0.5;
+            res_step *= 0.5;
+            gain_step *= 0.5;
+        }
+    }
+
+    Some(best)
+}
+
+pub fn execute(task: Task) {
+    match task {
+        Task::MatchCurve {
+            params,
+            executor,
+            gui_context,
+        } => {
+            let target = params.editor.target_curve.lock().unwrap().clone();
+
+            if let Some(fit) = fit(Setup::new(&params), &target) {
+                executor.execute_gui(Task::ApplyMatch {
+                    params,
+                    fit,
+                    gui_context,
+                });
+            }
+        }
+        Task::ApplyMatch {
+            params,
+            fit,
+            gui_context,
+        } => {
+            let setter = ParamSetter::new(gui_context.as_ref());
+            let changes = [
+                (&params.cutoff, fit.cutoff),
+                (&params.res, params.res_normalized(fit.res)),
+                (&params.gain, params.gain.preview_normalized(fit.gain_db)),
+            ];
+
+            // a single gesture, like the editor's own multi-parameter edits, so that hosts
+            // record, and undo, the match as one step
+            for (param, _) in changes {
+                setter.begin_set_parameter(param);
+            }
+            for (param, normalized) in changes {
+                setter.set_parameter_normalized(param, normalized);
+            }
+            for (param, _) in changes {
+                setter.end_set_parameter(param);
+            }
+        }
+        Task::ExportPlot { params } => {
+            let snapshot = params.editor.plot_snapshot.lock().unwrap().clone();
+
+            match export::write_svg(&snapshot) {
+                Ok(path) => nih_log!("exported the response to {}", path.display()),
+                Err(err) => nih_error!("failed to export the response: {err}"),
+            }
+        }
+        #[cfg(feature = "param-log")]
+        Task::LogParams {
+            pos_samples,
+            mode,
+            cutoff_hz,
+            res,
+            gain_db,
+        } => {
+            let time = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+            let pos = pos_samples.map_or_else(|| "-".to_string(), |pos| pos.to_string());
+
+            nih_log!(
+                "[{time:.3}] sample {pos}: {}, {cutoff_hz:.1} Hz, res {res:.3}, {gain_db:+.2} dB",
+                Mode::variants()[mode.to_index()],
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_the_whole_cascade() {
+        let params = SVFParams::default();
+        let cascade = Setup {
+            mode: Mode::LowPass,
+            num_stages: 4,
+            ..Setup::new(&params)
+        };
+        let truth = Fit {
+            cutoff: 0.6,
+            res: 0.3,
+            gain_db: 0.,
+        };
+
+        let cutoff_hz = params
+            .cutoff_range
+            .cutoff_hz(truth.cutoff, cascade.max_freq);
+        let cutoff = cascade.prewarp.analog(cutoff_hz * cascade.two_pi_tick);
+        let scale = params.res_compensation(Mode::LowPass, truth.res, 4);
+        let target: Vec<_> = (0..64)
+            .map(|i| {
+                let freq = 20. * 1000f32.powf(i as f32 / 63.);
+                let w = freq * cascade.two_pi_tick;
+                let h = transfer_function(Mode::LowPass.filter_mode(), 4, w, cutoff, truth.res, 1.);
+                (freq, 20. * (h.norm() * scale).log10().max(-6.))
+            })
+            .collect();
+
+        assert!(error(cascade, &target, truth) < 1e-6);
+        let single = Setup {
+            num_stages: 1,
+            ..cascade
+        };
+        assert!(error(single, &target, truth) > 1.);
+
+        let fit = fit(cascade, &target).unwrap();
+        assert!((fit.cutoff - truth.cutoff).abs() < 0.01, "{}", fit.cutoff);
+        assert!((fit.res - truth.res).abs() < 0.02, "{}", fit.res);
+    }
+}
@@ -0,0 +1,214 @@
+use core::f32::consts::FRAC_1_SQRT_2;
+
+use nih_plug::prelude::Enum;
+use plugin_util::filter::svf::FilterMode;
+
+/// The filter modes exposed to the host. Wraps `plugin_util`'s `FilterMode` so that
+/// every variant gets an unambiguous display name and a stable ID for automation
+/// and presets, regardless of how the underlying enum evolves.
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    #[id = "lp"]
+    #[name = "Low Pass"]
+    LowPass,
+    #[id = "bp"]
+    #[name = "Band Pass"]
+    BandPass,
+    #[id = "bp1"]
+    #[name = "Band Pass (Unit Gain)"]
+    UnitBandPass,
+    #[id = "hp"]
+    #[name = "High Pass"]
+    HighPass,
+    #[id = "ap"]
+    #[name = "All Pass"]
+    AllPass,
+    #[id = "notch"]
+    #[name = "Notch"]
+    Notch,
+    #[id = "low_shelf"]
+    #[name = "Low Shelf"]
+    LowShelf,
+    #[id = "bell"]
+    #[name = "Bell"]
+    Bell,
+    #[id = "high_shelf"]
+    #[name = "High Shelf"]
+    HighShelf,
+    /// A high shelf, with its output scaled so that its low and high ends are cut
+    /// and boosted by half the gain each, pivoting at the cutoff
+    #[id = "tilt"]
+    #[name = "Tilt"]
+    Tilt,
+}
+
+impl Mode {
+    pub fn filter_mode(self) -> FilterMode {
+        match self {
+            Self::LowPass => FilterMode::LP,
+            Self::BandPass => FilterMode::BP,
+            Self::UnitBandPass => FilterMode::BP1,
+            Self::HighPass => FilterMode::HP,
+            Self::AllPass => FilterMode::AP,
+            Self::Notch => FilterMode::NCH,
+            Self::LowShelf => FilterMode::LSH,
+            Self::Bell => FilterMode::BSH,
+            Self::HighShelf | Self::Tilt => FilterMode::HSH,
+        }
+    }
+
+    /// Whether the `gain` parameter has any effect on this mode.
+    pub fn uses_gain(self) -> bool {
+        matches!(
+            self,
+            Self::LowShelf | Self::Bell | Self::HighShelf | Self::Tilt
+        )
+    }
+
+    /// The peak gain the (editor-side) resonance `res` gives this mode's response, 1 for the
+    /// modes whose peak never exceeds unity, or is set by the gain.
+    pub fn resonance_peak(self, res: f32) -> f32 {
+        match self {
+            Self::LowPass | Self::HighPass if res < FRAC_1_SQRT_2 => {
+                1. / (2. * res * (1. - res * res).sqrt())
+            }
+            Self::BandPass => 1. / (2. * res),
+            _ => 1.,
+        }
+    }
+
+    /// Factor applied to the filter's output, for modes built on top of another one.
+    pub fn output_scale(self, gain: f32) -> f32 {
+        match self {
+            Self::Tilt => gain.sqrt().recip(),
+            _ => 1.,
+        }
+    }
+}
+
+/// Dips the output out in the mode the filter runs in, then back in once it has switched to
+/// the requested one, so that mode changes, automated or not, don't click.
+#[derive(Default)]
+pub struct ModeFade {
+    active: Mode,
+    gain: f32,
+    target: f32,
+    step: f32,
+}
+
+impl ModeFade {
+    /// Switches straight to `mode`, at full gain.
+    pub fn reset(&mut self, mode: Mode) {
+        self.active = mode;
+        self.gain = 1.;
+        self.target = 1.;
+    }
+
+    /// The mode the filter runs in, trailing the requested one until the output has faded out.
+    pub fn active(&self) -> Mode {
+        self.active
+    }
+
+    /// Heads for `requested`, the gain moving by `step` per sample. Called once per block,
+    /// so that the switch itself happens at a block boundary.
+    pub fn request(&mut self, requested: Mode, step: f32) {
+        if requested != self.active && self.gain <= 0. {
+            self.active = requested;
+        }
+        self.target = if requested == self.active { 1. } else { 0. };
+        self.step = step;
+    }
+
+    /// The output's gain for the next sample.
+    pub fn next(&mut self) -> f32 {
+        if self.gain != self.target {
+            self.gain = if self.target > self.gain {
+                (self.gain + self.step).min(self.target)
+            } else {
+                (self.gain - self.step).max(self.target)
+            };
+        }
+        self.gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plugin_util::simd::*;
+
+    use super::*;
+    use crate::{res_to_damping, Filter, BASE_SAMPLE_RATE, MODE_FADE_MS, TAU};
+
+    /// Steps a 200 Hz sine, through a filter at 1 kHz, through every mode, switching every
+    /// few blocks, either crossfading or switching outright. Returns the largest difference
+    /// between two consecutive output samples.
+    fn automate_modes(crossfade: bool) -> f32 {
+        const BLOCK_LEN: usize = 64;
+
+        let w = TAU * 200. / BASE_SAMPLE_RATE;
+        let w_c = Simd::splat(TAU * 1000. / BASE_SAMPLE_RATE);
+        let k = Simd::splat(res_to_damping(0.5));
+        let gain = 2f32;
+        let step = 1000. / MODE_FADE_MS / BASE_SAMPLE_RATE;
+
+        let mut filter = Filter::default();
+        let mut fade = ModeFade::default();
+        fade.reset(Mode::default());
+        Filter::get_update_function(fade.active().filter_mode())(
+            &mut filter,
+            w_c,
+            k,
+            Simd::splat(gain),
+        );
+
+        let mut n = 0;
+        let mut last = 0f32;
+        let mut max_jump = 0f32;
+
+        for mode in (0..Mode::variants().len())
+            .cycle()
+            .take(30)
+            .map(Mode::from_index)
+        {
+            for _ in 0..8 {
+                if crossfade {
+                    fade.request(mode, step);
+                } else {
+                    fade.reset(mode);
+                }
+                let active = fade.active();
+                Filter::get_smoothing_update_function(active.filter_mode())(
+                    &mut filter,
+                    w_c,
+                    k,
+                    Simd::splat(gain),
+                    Simd::splat(1. / BLOCK_LEN as f32),
+                );
+                let get_output = Filter::get_output_function(active.filter_mode());
+
+                for _ in 0..BLOCK_LEN {
+                    filter.update_all_smoothers();
+                    filter.process(Simd::splat(f32::sin(w * n as f32)));
+                    let output = get_output(&filter)[0] * active.output_scale(gain) * fade.next();
+
+                    max_jump = max_jump.max((output - last).abs());
+                    last = output;
+                    n += 1;
+                }
+            }
+        }
+
+        max_jump
+    }
+
+    #[test]
+    fn automated_mode_changes_are_click_free() {
+        // the sine itself moves by at most ~0.06 per sample, at the shelves' gain
+        let crossfaded = automate_modes(true);
+        assert!(crossfaded < 0.1, "{crossfaded}");
+
+        let switched = automate_modes(false);
+        assert!(switched > 0.1, "{switched}");
+    }
+}
@@ -0,0 +1,27 @@
+use plugin_util::simd::*;
+
+/// Topology-preserving-transform one-pole lowpass.
+#[derive(Default)]
+pub struct OnePole {
+    g: f32x2,
+    s: f32x2,
+}
+
+impl OnePole {
+    pub fn reset(&mut self) {
+        self.s = Simd::splat(0.);
+    }
+
+    /// `w_c` is the cutoff frequency in radians per sample.
+    pub fn set_cutoff(&mut self, w_c: f32) {
+        let g = f32::tan(w_c * 0.5);
+        self.g = Simd::splat(g / (1. + g));
+    }
+
+    pub fn process(&mut self, sample: f32x2) -> f32x2 {
+        let v = (sample - self.s) * self.g;
+        let y = v + self.s;
+        self.s = y + v;
+        y
+    }
+}
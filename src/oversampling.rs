@@ -0,0 +1,277 @@
+use core::f32::consts::PI;
+
+use nih_plug::prelude::Enum;
+use plugin_util::simd::*;
+
+pub const MAX_TAPS: usize = 63;
+pub const MAX_RATIO: usize = 4;
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OversamplingFactor {
+    #[default]
+    #[name = "1x"]
+    X1,
+    #[name = "2x"]
+    X2,
+    #[name = "4x"]
+    X4,
+}
+
+impl OversamplingFactor {
+    pub fn num_stages(self) -> usize {
+        match self {
+            Self::X1 => 0,
+            Self::X2 => 1,
+            Self::X4 => 2,
+        }
+    }
+
+    pub fn ratio(self) -> usize {
+        1 << self.num_stages()
+    }
+}
+
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OversamplingQuality {
+    #[name = "Eco"]
+    Eco,
+    #[default]
+    #[name = "Standard"]
+    Standard,
+    #[name = "High"]
+    High,
+}
+
+impl OversamplingQuality {
+    pub fn num_taps(self) -> usize {
+        match self {
+            Self::Eco => 15,
+            Self::Standard => 31,
+            Self::High => 63,
+        }
+    }
+}
+
+/// Latency, in samples at the base rate, of a full up/downsampling round trip.
+pub fn latency(factor: OversamplingFactor, quality: OversamplingQuality) -> u32 {
+    // every (linear phase) halfband stage delays by (n - 1) / 2 samples at the rate it runs
+    // at, and each up/down pair runs at twice the rate of the previous pair. That's an odd
+    // number of samples at the base rate for the 2x pair, and half of one for the 4x pair,
+    // which the oversampler pads out to a whole sample, keeping the total exact
+    let half_len = (quality.num_taps() as u32 - 1) / 2;
+
+    match factor {
+        OversamplingFactor::X1 => 0,
+        OversamplingFactor::X2 => half_len,
+        OversamplingFactor::X4 => half_len + (half_len + 1) / 2,
+    }
+}
+
+/// Blackman-windowed sinc halfband lowpass, running on stereo frames. Every other tap, bar the
+/// center one, is zero, and skipped. `num_taps` is always 3 more than a multiple of 4, so that
+/// those are the odd ones.
+struct HalfBand {
+    kernel: [f32; MAX_TAPS],
+    num_taps: usize,
+    history: [f32x2; MAX_TAPS],
+    pos: usize,
+}
+
+impl Default for HalfBand {
+    fn default() -> Self {
+        let mut stage = Self {
+            kernel: [0.; MAX_TAPS],
+            num_taps: 0,
+            history: [Simd::splat(0.); MAX_TAPS],
+            pos: 0,
+        };
+        stage.design(OversamplingQuality::default().num_taps());
+        stage
+    }
+}
+
+impl HalfBand {
+    fn design(&mut self, num_taps: usize) {
+        let center = (num_taps - 1) as f32 / 2.;
+        let mut sum = 0.;
+
+        for (n, tap) in self.kernel[..num_taps].iter_mut().enumerate() {
+            let x = (n as f32 - center) * PI / 2.;
+            // exactly 0 at the (nonzero) multiples of pi, instead of rounding errors
+            let sinc = if x == 0. {
+                1.
+            } else if n % 2 == 1 {
+                0.
+            } else {
+                x.sin() / x
+            };
+
+            let phase = 2. * PI * n as f32 / (num_taps - 1) as f32;
+            let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2. * phase).cos();
+
+            *tap = sinc * window;
+            sum += *tap;
+        }
+
+        self.kernel[..num_taps]
+            .iter_mut()
+            .for_each(|tap| *tap /= sum);
+
+        self.num_taps = num_taps;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.history = [Simd::splat(0.); MAX_TAPS];
+        self.pos = 0;
+    }
+
+    fn push(&mut self, sample: f32x2) {
+        self.pos = self.pos.checked_sub(1).unwrap_or(self.num_taps - 1);
+        self.history[self.pos] = sample;
+    }
+
+    fn output(&self) -> f32x2 {
+        // history[pos] is the newest sample, history[pos - 1] (wrapping around) the oldest
+        let (oldest, newest) = self.history[..self.num_taps].split_at(self.pos);
+        let center = (self.num_taps - 1) / 2;
+        let center_sample = self.history[(self.pos + center) % self.num_taps];
+
+        newest
+            .iter()
+            .chain(oldest)
+            .zip(&self.kernel)
+            .step_by(2)
+            .fold(
+                center_sample * Simd::splat(self.kernel[center]),
+                |acc, (&x, &h)| acc + x * Simd::splat(h),
+            )
+    }
+
+    fn upsample(&mut self, sample: f32x2) -> [f32x2; 2] {
+        // zero-stuffing halves the signal's level, make up for it
+        self.push(sample * Simd::splat(2.));
+        let first = self.output();
+        self.push(Simd::splat(0.));
+        [first, self.output()]
+    }
+
+    fn downsample(&mut self, [first, second]: [f32x2; 2]) -> f32x2 {
+        self.push(first);
+        self.push(second);
+        self.output()
+    }
+}
+
+/// Cascaded 2x halfband up/downsampler. `up[i]` and `down[i]` convert between
+/// `2^i` and `2^(i + 1)` times the base rate.
+#[derive(Default)]
+pub struct Oversampler {
+    up: [HalfBand; 2],
+    down: [HalfBand; 2],
+    /// The last sample at twice the base rate, delayed by one, half a sample at the base
+    /// rate, when oversampling 4x, for a whole number of samples of latency
+    pad: f32x2,
+}
+
+impl Oversampler {
+    pub fn set_quality(&mut self, quality: OversamplingQuality) {
+        for stage in self.up.iter_mut().chain(&mut self.down) {
+            stage.design(quality.num_taps());
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.up
+            .iter_mut()
+            .chain(&mut self.down)
+            .for_each(HalfBand::reset);
+        self.pad = Simd::splat(0.);
+    }
+
+    /// Writes `factor.ratio()` samples, at the oversampled rate, to the start of `out`.
+    pub fn upsample(
+        &mut self,
+        factor: OversamplingFactor,
+        sample: f32x2,
+        out: &mut [f32x2; MAX_RATIO],
+    ) {
+        out[0] = sample;
+        let mut len = 1;
+
+        for (n, stage) in self.up[..factor.num_stages()].iter_mut().enumerate() {
+            let input = *out;
+            for (i, &sample) in input[..len].iter().enumerate() {
+                [out[2 * i], out[2 * i + 1]] = stage.upsample(sample);
+            }
+            len *= 2;
+
+            if n == 0 && factor == OversamplingFactor::X4 {
+                let next = out[1];
+                out[1] = out[0];
+                out[0] = core::mem::replace(&mut self.pad, next);
+            }
+        }
+    }
+
+    /// Reads `factor.ratio()` samples, at the oversampled rate, from the start of `samples`.
+    pub fn downsample(
+        &mut self,
+        factor: OversamplingFactor,
+        samples: &mut [f32x2; MAX_RATIO],
+    ) -> f32x2 {
+        let mut len = factor.ratio();
+
+        for stage in self.down[..factor.num_stages()].iter_mut().rev() {
+            len /= 2;
+            for i in 0..len {
+                samples[i] = stage.downsample([samples[2 * i], samples[2 * i + 1]]);
+            }
+        }
+
+        samples[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse_peaks_at_reported_latency() {
+        for factor in [OversamplingFactor::X2, OversamplingFactor::X4] {
+            for quality in [
+                OversamplingQuality::Eco,
+                OversamplingQuality::Standard,
+                OversamplingQuality::High,
+            ] {
+                let mut oversampler = Oversampler::default();
+                oversampler.set_quality(quality);
+
+                let output: Vec<f32> = (0..128)
+                    .map(|n| {
+                        let mut frames = [Simd::splat(0.); MAX_RATIO];
+                        let sample = if n == 0 { 1. } else { 0. };
+                        oversampler.upsample(factor, Simd::splat(sample), &mut frames);
+                        oversampler.downsample(factor, &mut frames)[0]
+                    })
+                    .collect();
+
+                let peak = (0..output.len())
+                    .max_by(|&a, &b| output[a].abs().total_cmp(&output[b].abs()))
+                    .unwrap();
+
+                assert_eq!(
+                    peak as u32,
+                    latency(factor, quality),
+                    "{factor:?}, {quality:?}"
+                );
+                // linear phase, the response is symmetric around a whole sample of delay
+                for j in 1..8 {
+                    let (early, late) = (output[peak - j], output[peak + j]);
+                    assert!((early - late).abs() < 1e-5, "{factor:?}, {quality:?}: {j}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(feature = "gui")]
+use num::Complex;
+use plugin_util::{filter::svf::FilterMode, simd::*};
+
+use crate::{Filter, TAU};
+
+/// A pair of broad allpasses straddling the cutoff, filling in the group delay around the
+/// resonant peak so that the total delay is flatter across it. The magnitude is untouched.
+#[derive(Default)]
+pub struct PhaseEq {
+    stages: [Filter; 2],
+}
+
+impl PhaseEq {
+    pub fn reset(&mut self) {
+        self.stages.iter_mut().for_each(Filter::reset);
+    }
+
+    /// Cutoffs and damping of the stages, for the filter's cutoff `w_c` and damping `k`.
+    /// They sit roughly the peak's bandwidth apart, and narrow towards the
+    /// filter's own damping with `amount`.
+    fn stage_params(w_c: f32x2, k: f32x2, amount: f32) -> ([f32x2; 2], f32x2) {
+        let offset = k.to_array().map(f32::exp2);
+        let offset = Simd::from_array(offset);
+        let max = Simd::splat(0.95 * TAU / 2.);
+
+        let cutoffs = [(w_c / offset).simd_min(max), (w_c * offset).simd_min(max)];
+        let damping =
+            k + (Simd::splat(2.) - k).simd_max(Simd::splat(0.)) * Simd::splat(1. - amount);
+
+        (cutoffs, damping)
+    }
+
+    pub fn update(&mut self, w_c: f32x2, k: f32x2, amount: f32, inc: f32x2) {
+        let (cutoffs, damping) = Self::stage_params(w_c, k, amount);
+
+        let update = Filter::get_smoothing_update_function(FilterMode::AP);
+        for (stage, w) in self.stages.iter_mut().zip(cutoffs) {
+            update(stage, w, damping, Simd::splat(1.), inc);
+        }
+    }
+
+    pub fn prime(&mut self, w_c: f32x2, k: f32x2, amount: f32) {
+        let (cutoffs, damping) = Self::stage_params(w_c, k, amount);
+
+        let update = Filter::get_update_function(FilterMode::AP);
+        for (stage, w) in self.stages.iter_mut().zip(cutoffs) {
+            update(stage, w, damping, Simd::splat(1.));
+        }
+    }
+
+    pub fn process(&mut self, sample: f32x2) -> f32x2 {
+        let get_output = Filter::get_output_function(FilterMode::AP);
+
+        self.stages.iter_mut().fold(sample, |y, stage| {
+            stage.update_all_smoothers();
+            stage.process(y);
+            get_output(stage)
+        })
+    }
+
+    /// Response of both stages at the frequency `w`, in radians per sample, `res`
+    /// being the editor-side resonance.
+    #[cfg(feature = "gui")]
+    pub fn transfer_function(w: f32, w_c: f32, res: f32, amount: f32) -> Complex<f32> {
+        let h = Filter::get_transfer_function::<f32>(FilterMode::AP);
+        let k = crate::res_to_damping(res);
+        let (cutoffs, damping) = Self::stage_params(Simd::splat(w_c), Simd::splat(k), amount);
+        let res = crate::damping_to_res(damping[0]);
+
+        cutoffs
+            .into_iter()
+            .map(|cutoff| {
+                let s = Complex::new(0., f32::tan(w * 0.5) / f32::tan(cutoff[0] * 0.5));
+                h(s, res, 1.)
+            })
+            .product()
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(feature = "gui")]
+use num::Complex;
+use plugin_util::{filter::svf::FilterMode, simd::*};
+
+use crate::{Filter, TAU};
+
+pub const MAX_STAGES: usize = 8;
+
+/// A chain of SVF allpasses at a common, LFO-swept cutoff, mixed back with its input.
+#[derive(Default)]
+pub struct Phaser {
+    stages: [Filter; MAX_STAGES],
+    lfo_phase: f32,
+    feedback_sample: f32x2,
+}
+
+impl Phaser {
+    pub fn reset(&mut self) {
+        self.stages.iter_mut().for_each(Filter::reset);
+        self.lfo_phase = 0.;
+        self.feedback_sample = Simd::splat(0.);
+    }
+
+    /// The cutoff multiplier applied to the main cutoff by the LFO, in octaves.
+    pub fn sweep_octaves(&self, depth: f32) -> f32 {
+        depth * f32::sin(self.lfo_phase * TAU)
+    }
+
+    /// Advances the LFO by `num_samples` and retargets every stage accordingly.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        num_samples: usize,
+        two_pi_tick: f32,
+        rate: f32,
+        depth: f32,
+        w_c: f32x2,
+        res: f32x2,
+        inc: f32x2,
+    ) -> f32 {
+        self.lfo_phase = (self.lfo_phase + rate * num_samples as f32 * two_pi_tick / TAU).fract();
+
+        let octaves = self.sweep_octaves(depth);
+        let w = Self::swept_cutoff(w_c, octaves);
+
+        let update = Filter::get_smoothing_update_function(FilterMode::AP);
+        for stage in &mut self.stages {
+            update(stage, w, res, Simd::splat(1.), inc);
+        }
+
+        octaves
+    }
+
+    /// Sets every stage to the current sweep position instantly, without smoothing.
+    pub fn prime(&mut self, depth: f32, w_c: f32x2, res: f32x2) {
+        let w = Self::swept_cutoff(w_c, self.sweep_octaves(depth));
+
+        let update = Filter::get_update_function(FilterMode::AP);
+        for stage in &mut self.stages {
+            update(stage, w, res, Simd::splat(1.));
+        }
+    }
+
+    fn swept_cutoff(w_c: f32x2, octaves: f32) -> f32x2 {
+        (w_c * Simd::splat(octaves.exp2())).simd_min(Simd::splat(0.95 * TAU / 2.))
+    }
+
+    pub fn process(&mut self, sample: f32x2, num_stages: usize, feedback: f32) -> f32x2 {
+        let get_output = Filter::get_output_function(FilterMode::AP);
+
+        let mut y = sample + self.feedback_sample * Simd::splat(feedback);
+
+        let (active, inactive) = self.stages.split_at_mut(num_stages);
+
+        for stage in active {
+            stage.update_all_smoothers();
+            stage.process(y);
+            y = get_output(stage);
+        }
+
+        // keep the unused stages' ramps going, so they're on target once they're added back
+        inactive.iter_mut().for_each(Filter::update_all_smoothers);
+
+        self.feedback_sample = y;
+
+        (sample + y) * Simd::splat(0.5)
+    }
+
+    /// Response of the phaser at a prewarped, cutoff-normalized frequency `w`,
+    /// `delay` being the one-sample delay of the feedback path at that frequency.
+    #[cfg(feature = "gui")]
+    pub fn transfer_function(
+        w: f32,
+        delay: Complex<f32>,
+        res: f32,
+        num_stages: usize,
+        feedback: f32,
+    ) -> Complex<f32> {
+        let h = Filter::get_transfer_function::<f32>(FilterMode::AP);
+
+        let chain = h(Complex::new(0., w), res, 1.).powu(num_stages as u32);
+        let chain = chain / (1. - feedback * delay * chain);
+
+        (1. + chain) * 0.5
+    }
+}
@@ -0,0 +1,43 @@
+pub const MAX_VOICES: usize = 16;
+
+/// Per-voice (CLAP) polyphonic modulation offsets of a single parameter, summed,
+/// since the filter itself is monophonic.
+#[derive(Default)]
+pub struct PolyMod {
+    offsets: [Option<(i32, f32)>; MAX_VOICES],
+}
+
+impl PolyMod {
+    pub fn set(&mut self, voice_id: i32, normalized_offset: f32) {
+        let slot = self
+            .offsets
+            .iter()
+            .position(|slot| matches!(slot, Some((id, _)) if *id == voice_id))
+            .or_else(|| self.offsets.iter().position(Option::is_none));
+
+        // voices past the capacity we announced are ignored
+        if let Some(i) = slot {
+            self.offsets[i] = Some((voice_id, normalized_offset));
+        }
+    }
+
+    pub fn remove(&mut self, voice_id: i32) {
+        for slot in &mut self.offsets {
+            if matches!(slot, Some((id, _)) if *id == voice_id) {
+                *slot = None;
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn sum(&self) -> f32 {
+        self.offsets
+            .iter()
+            .flatten()
+            .map(|&(_, offset)| offset)
+            .sum()
+    }
+}
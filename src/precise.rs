@@ -0,0 +1,94 @@
+use core::simd::f64x2;
+use plugin_util::simd::*;
+
+use crate::biquad::Prototype;
+
+/// `g`, `k`, and how much of the input, band and low outputs make up the output
+type Coefficients = [f64x2; 5];
+
+/// The coefficients realizing each channel's `prototype` at the (prewarped) cutoff `w_c`.
+/// The denominator is normalized to the SVF's, `s` scaled so that its ends match.
+pub fn coefficients(prototypes: [Prototype; 2], w_c: f32x2) -> Coefficients {
+    let mut coeffs = [[0.; 2]; 5];
+
+    for (i, (num, den)) in prototypes.into_iter().enumerate() {
+        let [b0, b1, b2] = num.map(f64::from);
+        let [a0, a1, a2] = den.map(f64::from);
+
+        let sigma = (a0 / a2).sqrt();
+        let g = f64::tan(f64::from(w_c[i]) * 0.5) * sigma;
+        let k = a1 / (a0 * a2).sqrt();
+        let [n0, n1, n2] = [b0 / a0, b1 * sigma / a0, b2 * sigma * sigma / a0];
+
+        // the highpass output being the input minus `k` times the band and the low outputs
+        for (c, value) in coeffs.iter_mut().zip([g, k, n2, n1 - k * n2, n0 - n2]) {
+            c[i] = value;
+        }
+    }
+
+    coeffs.map(Simd::from_array)
+}
+
+/// The prototype of the band split mode: the input, plus `band_gain` times the unit
+/// bandpass band around the cutoff.
+pub fn band_split_prototype(k: f32, band_gain: f32) -> Prototype {
+    ([1., k * (1. + band_gain), 1.], [1., k, 1.])
+}
+
+/// A zero-delay feedback SVF running in double precision, converting at the boundaries, for
+/// offline rendering. The coefficients ramp linearly, like the plugin's.
+#[derive(Default)]
+pub struct PreciseFilter {
+    ic1: f64x2,
+    ic2: f64x2,
+    coeffs: Coefficients,
+    incs: Coefficients,
+}
+
+impl PreciseFilter {
+    pub fn reset(&mut self) {
+        self.ic1 = Simd::splat(0.);
+        self.ic2 = Simd::splat(0.);
+    }
+
+    /// Jumps straight to the coefficients of `prototypes` at `w_c`.
+    pub fn update(&mut self, prototypes: [Prototype; 2], w_c: f32x2) {
+        self.coeffs = coefficients(prototypes, w_c);
+        self.incs = [Simd::splat(0.); 5];
+    }
+
+    /// Ramps towards the coefficients of `prototypes` at `w_c`, by `inc` of the way per tick.
+    pub fn smoothing_update(&mut self, prototypes: [Prototype; 2], w_c: f32x2, inc: f32x2) {
+        let targets = coefficients(prototypes, w_c);
+        let inc = inc.cast::<f64>();
+
+        for ((inc_c, c), target) in self.incs.iter_mut().zip(self.coeffs).zip(targets) {
+            *inc_c = (target - c) * inc;
+        }
+    }
+
+    pub fn tick(&mut self) {
+        for (c, inc) in self.coeffs.iter_mut().zip(self.incs) {
+            *c += inc;
+        }
+    }
+
+    pub fn process(&mut self, sample: f32x2) -> f32x2 {
+        let [g, k, m0, m1, m2] = self.coeffs;
+        let one = Simd::splat(1.);
+        let two = Simd::splat(2.);
+
+        let a1 = one / (one + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v0 = sample.cast::<f64>();
+        let v3 = v0 - self.ic2;
+        let v1 = a1 * self.ic1 + a2 * v3;
+        let v2 = self.ic2 + a2 * self.ic1 + a3 * v3;
+        self.ic1 = two * v1 - self.ic1;
+        self.ic2 = two * v2 - self.ic2;
+
+        (m0 * v0 + m1 * v1 + m2 * v2).cast::<f32>()
+    }
+}
@@ -0,0 +1,42 @@
+use crate::mode::Mode;
+
+/// A starting point, setting the filter's main parameters.
+pub struct Preset {
+    pub name: &'static str,
+    pub mode: Mode,
+    pub cutoff_hz: f32,
+    /// editor-side, as expected by the transfer functions
+    pub res: f32,
+    pub gain_db: f32,
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "Rumble Remove",
+        mode: Mode::HighPass,
+        cutoff_hz: 80.,
+        res: 0.707,
+        gain_db: 0.,
+    },
+    Preset {
+        name: "Air",
+        mode: Mode::HighShelf,
+        cutoff_hz: 10000.,
+        res: 0.707,
+        gain_db: 4.,
+    },
+    Preset {
+        name: "Telephone",
+        mode: Mode::UnitBandPass,
+        cutoff_hz: 1500.,
+        res: 0.6,
+        gain_db: 0.,
+    },
+    Preset {
+        name: "Resonant Sweep",
+        mode: Mode::LowPass,
+        cutoff_hz: 400.,
+        res: 0.06,
+        gain_db: 0.,
+    },
+];
@@ -0,0 +1,39 @@
+/// Linear ramp towards a target, restarted whenever the target changes.
+#[derive(Default)]
+pub struct Ramp {
+    value: f32,
+    target: f32,
+    step: f32,
+}
+
+impl Ramp {
+    /// Jumps straight to `value`.
+    pub fn reset(&mut self, value: f32) {
+        self.value = value;
+        self.target = value;
+        self.step = 0.;
+    }
+
+    /// Ramps towards `target` over `len` samples, if it differs from the current one.
+    pub fn set_target(&mut self, target: f32, len: f32) {
+        if target != self.target {
+            self.target = target;
+            self.step = (target - self.value) / len.max(1.);
+        }
+    }
+
+    pub fn next(&mut self) -> f32 {
+        if self.value != self.target {
+            self.value += self.step;
+            let overshot = if self.step > 0. {
+                self.value > self.target
+            } else {
+                self.value < self.target
+            };
+            if overshot {
+                self.value = self.target;
+            }
+        }
+        self.value
+    }
+}
@@ -0,0 +1,292 @@
+use nih_plug::prelude::Enum;
+pub use plugin_util::filter::svf::FilterMode;
+use plugin_util::simd::*;
+
+use crate::{
+    batch::BatchFilter, biquad::analog_prototype, mode::Mode, res_to_damping, CutoffRange, Filter,
+    NYQUIST_MARGIN, SMOOTHING_MS, TAU,
+};
+
+/// Frames converted at once by `StereoFilter::process`
+const CHUNK_LEN: usize = 64;
+
+/// A mono filter, keeping its state across buffers, whose settings glide to new values
+/// over `SMOOTHING_MS`, as in the plugin.
+pub struct BufferFilter {
+    filter: Filter,
+    sample_rate: f32,
+    mode: Option<FilterMode>,
+    /// Samples left until the smoothers reach their targets
+    smoothing_remaining: usize,
+}
+
+impl BufferFilter {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            filter: Filter::default(),
+            sample_rate,
+            mode: None,
+            smoothing_remaining: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+    }
+
+    /// Sets the filter's cutoff, in Hz, its resonance, as on the plugin's knob, and the gain,
+    /// in dB, of the shelving and bell modes. Changing the mode jumps straight to the new
+    /// settings, without smoothing.
+    pub fn set(&mut self, cutoff_hz: f32, res: f32, gain_db: f32, mode: FilterMode) {
+        let range = CutoffRange::default();
+        let max_freq = range.max_hz.min(NYQUIST_MARGIN * self.sample_rate);
+        let w_c = Simd::splat(TAU * cutoff_hz.clamp(range.min_hz, max_freq) / self.sample_rate);
+        let k = Simd::splat(res_to_damping(res));
+        let gain = Simd::splat(10f32.powf(gain_db / 20.));
+
+        if self.mode == Some(mode) {
+            let len = (SMOOTHING_MS / 1000. * self.sample_rate).round().max(1.);
+            Filter::get_smoothing_update_function(mode)(
+                &mut self.filter,
+                w_c,
+                k,
+                gain,
+                Simd::splat(1. / len),
+            );
+            self.smoothing_remaining = len as usize;
+        } else {
+            Filter::get_update_function(mode)(&mut self.filter, w_c, k, gain);
+            self.mode = Some(mode);
+            self.smoothing_remaining = 0;
+        }
+    }
+
+    /// Filters `samples` in place. Passes them through untouched until `set` is first called.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let Some(mode) = self.mode else {
+            return;
+        };
+        let get_output = Filter::get_output_function(mode);
+
+        for sample in samples {
+            if self.smoothing_remaining > 0 {
+                self.filter.update_all_smoothers();
+                self.smoothing_remaining -= 1;
+            }
+            self.filter.process(Simd::splat(*sample));
+            *sample = get_output(&self.filter)[0];
+        }
+    }
+}
+
+/// A stereo filter, set like `BufferFilter`, processing two frames at a time once its settings
+/// have settled, through an SVF of its own rather than the plugin's.
+pub struct StereoFilter {
+    filter: BatchFilter,
+    sample_rate: f32,
+    mode: Option<FilterMode>,
+    /// Samples left until the coefficients reach their targets
+    smoothing_remaining: usize,
+}
+
+impl StereoFilter {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            filter: BatchFilter::default(),
+            sample_rate,
+            mode: None,
+            smoothing_remaining: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+    }
+
+    /// See `BufferFilter::set`.
+    pub fn set(&mut self, cutoff_hz: f32, res: f32, gain_db: f32, mode: FilterMode) {
+        let range = CutoffRange::default();
+        let max_freq = range.max_hz.min(NYQUIST_MARGIN * self.sample_rate);
+        let w_c = Simd::splat(TAU * cutoff_hz.clamp(range.min_hz, max_freq) / self.sample_rate);
+        // the shelves' output isn't scaled, as with `BufferFilter`, so the first match will do
+        let prototype_mode = (0..Mode::variants().len())
+            .map(Mode::from_index)
+            .find(|m| m.filter_mode() == mode)
+            .unwrap_or_default();
+        let prototype = analog_prototype(
+            prototype_mode,
+            res_to_damping(res),
+            10f32.powf(gain_db / 20.),
+        );
+
+        if self.mode == Some(mode) {
+            let len = (SMOOTHING_MS / 1000. * self.sample_rate).round().max(1.);
+            self.filter
+                .smoothing_update([prototype; 2], w_c, Simd::splat(1. / len));
+            self.smoothing_remaining = len as usize;
+        } else {
+            self.filter.update([prototype; 2], w_c);
+            self.mode = Some(mode);
+            self.smoothing_remaining = 0;
+        }
+    }
+
+    /// Filters `left` and `right`, of the same length, in place. Passes them through
+    /// untouched until `set` is first called.
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        debug_assert_eq!(left.len(), right.len());
+        if self.mode.is_none() {
+            return;
+        }
+
+        for (left, right) in left.chunks_mut(CHUNK_LEN).zip(right.chunks_mut(CHUNK_LEN)) {
+            let mut frames = [Simd::splat(0.); CHUNK_LEN];
+            let frames = &mut frames[..left.len()];
+            for (frame, (&l, &r)) in frames.iter_mut().zip(left.iter().zip(right.iter())) {
+                *frame = Simd::from_array([l, r]);
+            }
+
+            // one frame at a time while the coefficients ramp, two at a time once they're static
+            let ramp_len = self.smoothing_remaining.min(frames.len());
+            let (ramp, rest) = frames.split_at_mut(ramp_len);
+            for frame in ramp {
+                self.filter.tick();
+                *frame = self.filter.process(*frame);
+            }
+            self.smoothing_remaining -= ramp_len;
+            self.filter.process_block(rest);
+
+            for (frame, (l, r)) in frames.iter().zip(left.iter_mut().zip(right.iter_mut())) {
+                [*l, *r] = frame.to_array();
+            }
+        }
+    }
+}
+
+/// Filters `samples`, at `sample_rate`, in place, from silence, with fixed settings, see
+/// `BufferFilter::set`.
+pub fn process_buffer(
+    samples: &mut [f32],
+    sample_rate: f32,
+    cutoff_hz: f32,
+    res: f32,
+    gain_db: f32,
+    mode: FilterMode,
+) {
+    let mut filter = BufferFilter::new(sample_rate);
+    filter.set(cutoff_hz, res, gain_db, mode);
+    filter.process(samples);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn noise(len: usize) -> Vec<f32> {
+        let mut rng = Rng::new(5);
+        (0..len).map(|_| rng.next_f32()).collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn passes_through_until_set() {
+        let input = noise(512);
+        let mut output = input.clone();
+        BufferFilter::new(48000.).process(&mut output);
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn chunks_make_no_difference() {
+        let input = noise(4096);
+
+        let render = |chunk_len: usize| {
+            let mut filter = BufferFilter::new(48000.);
+            filter.set(500., 0.3, 0., FilterMode::LP);
+            let mut output = input.clone();
+            let (start, end) = output.split_at_mut(1000);
+
+            start.chunks_mut(chunk_len).for_each(|c| filter.process(c));
+            // glides over the rest
+            filter.set(5000., 0.1, 0., FilterMode::LP);
+            end.chunks_mut(chunk_len).for_each(|c| filter.process(c));
+            output
+        };
+
+        let whole = render(input.len());
+        for chunk_len in [1, 7, 64, 999] {
+            assert_eq!(render(chunk_len), whole, "chunks of {chunk_len}");
+        }
+    }
+
+    #[test]
+    fn lowpass_passes_lows_and_cuts_highs() {
+        let sample_rate = 48000.;
+        let tone = |freq: f32| -> Vec<f32> {
+            (0..48000)
+                .map(|n| f32::sin(TAU * freq * n as f32 / sample_rate))
+                .collect()
+        };
+
+        for (freq, min_db, max_db) in [(100., -0.1, 0.1), (10000., f32::NEG_INFINITY, -30.)] {
+            let input = tone(freq);
+            let mut output = input.clone();
+            process_buffer(&mut output, sample_rate, 1000., 0.5, 0., FilterMode::LP);
+
+            // past the filter's settling
+            let gain_db = 20. * f32::log10(rms(&output[4800..]) / rms(&input[4800..]));
+            assert!(
+                (min_db..max_db).contains(&gain_db),
+                "{freq} Hz: {gain_db} dB"
+            );
+        }
+    }
+
+    #[test]
+    fn stereo_filter_matches_the_plugins() {
+        let sample_rate = 48000.;
+        let (left, right) = (
+            noise(3001),
+            noise(3001).into_iter().rev().collect::<Vec<_>>(),
+        );
+
+        for mode in [Mode::LowPass, Mode::BandPass, Mode::HighPass, Mode::Bell] {
+            let mut stereo = StereoFilter::new(sample_rate);
+            let mut mono = [
+                BufferFilter::new(sample_rate),
+                BufferFilter::new(sample_rate),
+            ];
+            let (mut stereo_left, mut stereo_right) = (left.clone(), right.clone());
+            let mut mono_outputs = [left.clone(), right.clone()];
+
+            let (split_left, rest_left) = stereo_left.split_at_mut(1001);
+            let (split_right, rest_right) = stereo_right.split_at_mut(1001);
+            stereo.set(800., 0.3, 6., mode.filter_mode());
+            stereo.process(split_left, split_right);
+            // glides over part of the rest
+            stereo.set(3000., 0.5, -6., mode.filter_mode());
+            stereo.process(rest_left, rest_right);
+
+            for (filter, output) in mono.iter_mut().zip(&mut mono_outputs) {
+                let (start, end) = output.split_at_mut(1001);
+                filter.set(800., 0.3, 6., mode.filter_mode());
+                filter.process(start);
+                filter.set(3000., 0.5, -6., mode.filter_mode());
+                filter.process(end);
+            }
+
+            for (stereo, mono) in [stereo_left, stereo_right].iter().zip(&mono_outputs) {
+                // the two filters' coefficients may glide along different paths
+                let settled = (0..1001).chain(2500..stereo.len());
+                for n in settled {
+                    let (a, b) = (stereo[n], mono[n]);
+                    assert!((a - b).abs() < 1e-4, "{mode:?}[{n}]: {a} != {b}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+/// Running RMS level, the mean square smoothed by a one-pole.
+#[derive(Default)]
+pub struct Rms {
+    mean_square: f32,
+}
+
+impl Rms {
+    pub fn reset(&mut self) {
+        self.mean_square = 0.;
+    }
+
+    /// `coeff` as given by `Follower::coefficient`.
+    pub fn process(&mut self, square: f32, coeff: f32) {
+        self.mean_square = square + (self.mean_square - square) * coeff;
+    }
+
+    pub fn mean_square(&self) -> f32 {
+        self.mean_square
+    }
+}
@@ -0,0 +1,52 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use plugin_util::simd::*;
+
+/// A seed that differs between instances, even ones created at the same instant.
+pub fn instance_seed() -> u32 {
+    static INSTANCES: AtomicU32 = AtomicU32::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos());
+    let instance = INSTANCES.fetch_add(1, Ordering::Relaxed);
+
+    nanos ^ instance.wrapping_mul(0x9E37_79B9)
+}
+
+/// Xorshift32, plenty good enough for audio noise and cheap enough for the audio thread.
+pub struct Rng {
+    state: u32,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(0x9E37_79B9)
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        // xorshift gets stuck at 0
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Uniformly distributed in `[-1, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (2. / (1 << 24) as f32) - 1.
+    }
+
+    pub fn next_f32x2(&mut self) -> f32x2 {
+        Simd::from_array([self.next_f32(), self.next_f32()])
+    }
+}
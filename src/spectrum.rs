@@ -0,0 +1,109 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use nih_plug::prelude::AtomicF32;
+#[cfg(feature = "gui")]
+use num::Complex;
+
+#[cfg(feature = "gui")]
+use crate::TAU;
+
+pub const SPECTRUM_LEN: usize = 2048;
+
+/// The latest output samples (mono), for the editor's spectrum view,
+/// readable from any thread without locking.
+pub struct SpectrumTap {
+    samples: [AtomicF32; SPECTRUM_LEN],
+    pos: AtomicUsize,
+}
+
+impl Default for SpectrumTap {
+    fn default() -> Self {
+        Self {
+            samples: core::array::from_fn(|_| AtomicF32::new(0.)),
+            pos: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl SpectrumTap {
+    pub fn push(&self, sample: f32) {
+        let pos = self.pos.load(Ordering::Relaxed);
+        self.samples[pos].store(sample, Ordering::Relaxed);
+        self.pos.store((pos + 1) % SPECTRUM_LEN, Ordering::Relaxed);
+    }
+
+    /// Copies the stored samples into `out`, oldest first.
+    #[cfg(feature = "gui")]
+    pub fn snapshot(&self, out: &mut [f32; SPECTRUM_LEN]) {
+        let pos = self.pos.load(Ordering::Relaxed);
+        let (newest, oldest) = self.samples.split_at(pos);
+
+        for (sample, atomic) in out.iter_mut().zip(oldest.iter().chain(newest)) {
+            *sample = atomic.load(Ordering::Relaxed);
+        }
+    }
+}
+
+/// In-place radix-2 FFT, `buf`'s length being a power of two.
+#[cfg(feature = "gui")]
+pub fn fft(buf: &mut [Complex<f32>]) {
+    let len = buf.len();
+    debug_assert!(len.is_power_of_two());
+    if len < 2 {
+        return;
+    }
+
+    // bit-reversed order, for the butterflies to work in place
+    let shift = usize::BITS - len.trailing_zeros();
+    for i in 0..len {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    let mut size = 2;
+    while size <= len {
+        let half = size / 2;
+        for chunk in buf.chunks_exact_mut(size) {
+            let (even, odd) = chunk.split_at_mut(half);
+            for (k, (e, o)) in even.iter_mut().zip(odd).enumerate() {
+                // computed directly, accumulating them drifts over long transforms
+                let twiddle = Complex::from_polar(1., -TAU * k as f32 / size as f32);
+                let t = *o * twiddle;
+                *o = *e - t;
+                *e += t;
+            }
+        }
+        size *= 2;
+    }
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fft_matches_dft() {
+        const LEN: usize = 64;
+
+        let input: Vec<_> = (0..LEN)
+            .map(|n| {
+                let n = n as f32;
+                Complex::new(f32::sin(0.3 * n) + 0.25 * f32::cos(1.7 * n), 0.1 * n.sqrt())
+            })
+            .collect();
+
+        let mut output = input.clone();
+        fft(&mut output);
+
+        for (k, &bin) in output.iter().enumerate() {
+            let dft: Complex<f32> = input
+                .iter()
+                .enumerate()
+                .map(|(n, &x)| x * Complex::from_polar(1., -TAU * (k * n) as f32 / LEN as f32))
+                .sum();
+            assert!((bin - dft).norm() < 1e-3, "bin {k}: {bin} vs {dft}");
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use plugin_util::{filter::svf::FilterMode, simd::*};
+
+use nih_plug::prelude::Enum;
+use num::Complex;
+
+use crate::{
+    biquad::analog_prototype, denormal::SilenceGate, mode::Mode, precise::PreciseFilter, render,
+    res_to_damping, rng::Rng, smoothing_step, transfer_function, CutoffRange, Filter,
+    BASE_SAMPLE_RATE, MAX_RES, MIN_RES, NYQUIST_MARGIN, SILENCE_HOLD_MS, SMOOTHING_MS, TAU,
+};
+
+/// A logarithmic (exponential) sine sweep from `w_start` to `w_end`, in radians per sample.
+pub fn log_sweep(w_start: f32, w_end: f32, len: usize) -> Vec<f32> {
+    let ratio = (w_end / w_start).ln();
+    let scale = w_start * len as f32 / ratio;
+
+    (0..len)
+        .map(|n| {
+            let t = n as f32 / len as f32;
+            f32::sin(scale * ((t * ratio).exp() - 1.))
+        })
+        .collect()
+}
+
+/// Runs `input` through the plugin's filter, as `render::process_buffer` would at
+/// `BASE_SAMPLE_RATE`, with fixed settings. As in the plugin, `w_c` is in radians per sample
+/// and `res` is the editor-side resonance.
+pub fn render(mode: FilterMode, w_c: f32, res: f32, gain: f32, input: &[f32]) -> Vec<f32> {
+    let mut output = input.to_vec();
+    let cutoff_hz = w_c / TAU * BASE_SAMPLE_RATE;
+    render::process_buffer(
+        &mut output,
+        BASE_SAMPLE_RATE,
+        cutoff_hz,
+        res,
+        20. * gain.log10(),
+        mode,
+    );
+    output
+}
+
+/// As `render`, through the double precision SVF used when rendering offline.
+pub fn render_precise(mode: FilterMode, w_c: f32, res: f32, gain: f32, input: &[f32]) -> Vec<f32> {
+    let mode = (0..Mode::variants().len())
+        .map(Mode::from_index)
+        .find(|m| m.filter_mode() == mode)
+        .unwrap_or_default();
+
+    let prototype = analog_prototype(mode, res_to_damping(res), gain);
+    let mut filter = PreciseFilter::default();
+    filter.update([prototype; 2], Simd::splat(w_c));
+
+    input
+        .iter()
+        .map(|&sample| filter.process(Simd::splat(sample))[0])
+        .collect()
+}
+
+/// The error, in dB relative to the input, of the single and double precision SVFs, filtering
+/// a tone an octave below `w_c`, against the exact steady-state output. The former's, rising
+/// as `w_c` gets lower, is the noise floor the double precision path lowers.
+pub fn precision_noise_floor(mode: FilterMode, w_c: f32, res: f32, gain: f32) -> (f32, f32) {
+    // the reference in double precision, so as not to limit the latter's floor
+    let w = f64::from(w_c) * 0.5;
+    let s = Complex::new(0., f64::tan(w * 0.5) / f64::tan(w));
+    let h = Filter::get_transfer_function::<f64>(mode)(s, f64::from(res), f64::from(gain));
+    let (norm, arg) = (h.norm(), h.arg());
+
+    // long enough to settle, then measured over as many periods
+    let len = (400. * core::f64::consts::PI / w) as usize;
+    let input: Vec<f32> = (0..2 * len)
+        .map(|n| f64::sin(w * n as f64) as f32)
+        .collect();
+
+    let error_db = |output: Vec<f32>| {
+        let (error, signal) = (len..2 * len).fold((0., 0.), |(error, signal), n| {
+            let exact = norm * f64::sin(w * n as f64 + arg);
+            let x = f64::from(input[n]);
+            (
+                error + (f64::from(output[n]) - exact).powi(2),
+                signal + x * x,
+            )
+        });
+        (10. * f64::log10(error / signal)) as f32
+    };
+
+    (
+        error_db(render(mode, w_c, res, gain, &input)),
+        error_db(render_precise(mode, w_c, res, gain, &input)),
+    )
+}
+
+/// Measures the steady-state gain, in dB, of the filter at the frequency `w`, in
+/// radians per sample, by comparing the RMS levels of a rendered sine and its input.
+pub fn tone_response(mode: FilterMode, w_c: f32, res: f32, gain: f32, w: f32) -> f32 {
+    let len = (200. * TAU / w) as usize;
+    // long enough for the slowest (most resonant) settings to settle, their envelope
+    // decaying by a factor of e every `1 / (res * w_c)` samples
+    let settle = len.max((30. / (res * w_c)) as usize);
+
+    // in double precision, the phase of a long f32 sine drifting into audible noise
+    let w64 = f64::from(w);
+    let input: Vec<f32> = (0..settle + len)
+        .map(|n| f64::sin(w64 * n as f64) as f32)
+        .collect();
+    let output = render(mode, w_c, res, gain, &input);
+
+    let rms = |samples: &[f32]| {
+        let power = samples.iter().map(|&x| f64::from(x).powi(2)).sum::<f64>();
+        (power / samples.len() as f64).sqrt()
+    };
+
+    (20. * f64::log10(rms(&output[settle..]) / rms(&input[settle..]))) as f32
+}
+
+/// The gain, in dB, a zero-delay feedback (TPT) SVF has at the frequency `w`: the analog
+/// prototype's, through the bilinear transform, prewarped at `w_c`. `tone_response` should
+/// match it at any setting, including high cutoffs and resonances, a naive design wouldn't.
+pub fn analytic_response(mode: FilterMode, w_c: f32, res: f32, gain: f32, w: f32) -> f32 {
+    let h = transfer_function(mode, 1, w, f32::tan(w_c * 0.5), res, gain);
+    10. * f32::log10(h.norm_sqr())
+}
+
+/// Renders `num_blocks` blocks of white noise through an SVF whose mode, cutoff, resonance
+/// and gain jump to random values every block, smoothed as in the plugin. Returns the peak
+/// absolute output, infinite if it ever stopped being finite. Reproducible for a given `seed`.
+pub fn automation_stress(seed: u32, num_blocks: usize, block_len: usize) -> f32 {
+    let mut rng = Rng::new(seed);
+    let mut unit = move || 0.5 * (rng.next_f32() + 1.);
+    let range = CutoffRange::default();
+    let mut filter = Filter::default();
+    let mut peak: f32 = 0.;
+
+    for _ in 0..num_blocks {
+        let mode =
+            Mode::from_index((unit() * Mode::variants().len() as f32) as usize).filter_mode();
+        let cutoff_hz = range.cutoff_hz(unit(), NYQUIST_MARGIN * BASE_SAMPLE_RATE);
+        let w_c = TAU * cutoff_hz / BASE_SAMPLE_RATE;
+        let res = MAX_RES * (MIN_RES / MAX_RES).powf(unit());
+        let gain = 10f32.powf((48. * unit() - 24.) / 20.);
+
+        Filter::get_smoothing_update_function(mode)(
+            &mut filter,
+            Simd::splat(w_c),
+            Simd::splat(res_to_damping(res)),
+            Simd::splat(gain),
+            Simd::splat(1. / block_len as f32),
+        );
+        let get_output = Filter::get_output_function(mode);
+
+        for _ in 0..block_len {
+            filter.update_all_smoothers();
+            filter.process(Simd::splat(unit() * 2. - 1.));
+            let sample = get_output(&filter)[0];
+
+            if !sample.is_finite() {
+                return f32::INFINITY;
+            }
+            peak = peak.max(sample.abs());
+        }
+    }
+
+    peak
+}
+
+/// How long, in seconds, the coefficient ramps take to reach a new target, at the default
+/// smoothing time, at `sample_rate` and blocks of `block_len` samples. Should always be
+/// `SMOOTHING_MS`.
+pub fn smoothing_duration(sample_rate: f32, block_len: usize) -> f32 {
+    let len = (SMOOTHING_MS / 1000. * sample_rate).round();
+    let mut remaining = 0.;
+    let mut ticks = smoothing_step(&mut remaining, len, true, block_len).1;
+
+    while remaining > 0. {
+        ticks += smoothing_step(&mut remaining, len, false, block_len).1;
+    }
+
+    ticks as f32 / sample_rate
+}
+
+/// Renders `burst_len` samples of white noise then silence through an SVF in `mode`, gated
+/// as in the plugin. Returns how many samples after the burst it took for the output to
+/// become exactly zero, if it did within `max_len` samples.
+pub fn decay_samples(
+    mode: FilterMode,
+    w_c: f32,
+    res: f32,
+    burst_len: usize,
+    max_len: usize,
+) -> Option<usize> {
+    let mut rng = Rng::new(1);
+    let mut filter = Filter::default();
+    let mut gate = SilenceGate::default();
+    let hold = (SILENCE_HOLD_MS / 1000. * BASE_SAMPLE_RATE) as usize;
+
+    Filter::get_update_function(mode)(
+        &mut filter,
+        Simd::splat(w_c),
+        Simd::splat(res_to_damping(res)),
+        Simd::splat(1.),
+    );
+    let get_output = Filter::get_output_function(mode);
+
+    for n in 0..burst_len + max_len {
+        let input = if n < burst_len {
+            rng.next_f32x2()
+        } else {
+            Simd::splat(0.)
+        };
+
+        filter.process(input);
+        let mut output = get_output(&filter);
+
+        if gate.process(input, output, hold) {
+            filter.reset();
+        }
+        if gate.is_closed(hold) {
+            output = Simd::splat(0.);
+        }
+
+        if n >= burst_len && output == Simd::splat(0.) && gate.is_closed(hold) {
+            return Some(n - burst_len);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// First samples of the impulse responses at 1 kHz and a resonance of 0.5, from the
+    /// bilinear transform of the analog prototypes, in double precision.
+    const GOLDEN_IMPULSE_RESPONSES: [(Mode, [f32; 8]); 3] = [
+        (
+            Mode::LowPass,
+            [
+                0.004730417,
+                0.018205,
+                0.03427905,
+                0.04757336,
+                0.05820492,
+                0.06632561,
+                0.07211465,
+                0.07577164,
+            ],
+        ),
+        (
+            Mode::BandPass,
+            [
+                0.06629071, 0.1225382, 0.1027189, 0.08358373, 0.06540397, 0.04839695, 0.032729,
+                0.01851903,
+            ],
+        ),
+        (
+            Mode::HighPass,
+            [
+                0.9289789,
+                -0.1407432,
+                -0.136998,
+                -0.1311571,
+                -0.1236089,
+                -0.1147226,
+                -0.1048436,
+                -0.09429068,
+            ],
+        ),
+    ];
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn impulse_responses_match_golden_values() {
+        let w_c = TAU * 1000. / BASE_SAMPLE_RATE;
+        let mut impulse = [0.; 8];
+        impulse[0] = 1.;
+
+        for (mode, golden) in GOLDEN_IMPULSE_RESPONSES {
+            let output = render(mode.filter_mode(), w_c, 0.5, 1., &impulse);
+            for (n, (&y, expected)) in output.iter().zip(golden).enumerate() {
+                assert!(
+                    (y - expected).abs() < 1e-5,
+                    "{mode:?}[{n}]: {y} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lowpass_sweep_passes_lows_and_cuts_highs() {
+        let len = 1 << 16;
+        let w_start = TAU * 20. / BASE_SAMPLE_RATE;
+        let w_end = TAU * 20000. / BASE_SAMPLE_RATE;
+        let input = log_sweep(w_start, w_end, len);
+        let output = render(
+            FilterMode::LP,
+            TAU * 1000. / BASE_SAMPLE_RATE,
+            0.5,
+            1.,
+            &input,
+        );
+
+        let gain_db = |range: core::ops::Range<usize>| {
+            20. * f32::log10(rms(&output[range.clone()]) / rms(&input[range]))
+        };
+
+        // skipping the very start, where the filter is still settling
+        let lows = gain_db(len / 100..len / 20);
+        assert!(lows.abs() < 0.1, "{lows} dB");
+        let highs = gain_db(len - len / 20..len);
+        assert!(highs < -40., "{highs} dB");
+    }
+
+    #[test]
+    fn tones_match_the_analytic_response() {
+        for mode in (0..Mode::variants().len()).map(Mode::from_index) {
+            for cutoff in [200., 1000., 15000., 19000.] {
+                let w_c = TAU * cutoff / BASE_SAMPLE_RATE;
+
+                for res in [MIN_RES, 0.5] {
+                    for ratio in [0.25, 0.5, 1., 2.] {
+                        let w = w_c * ratio;
+                        if w > 0.95 * core::f32::consts::PI {
+                            continue;
+                        }
+
+                        let expected = analytic_response(mode.filter_mode(), w_c, res, 2., w);
+                        // relative errors blow up in the deep cut of notches and slopes
+                        if expected < -40. {
+                            continue;
+                        }
+                        let measured = tone_response(mode.filter_mode(), w_c, res, 2., w);
+                        assert!(
+                            (measured - expected).abs() < 0.1,
+                            "{mode:?}, {cutoff} Hz, res {res}, x{ratio}: \
+                            {measured} dB != {expected} dB"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn double_precision_matches_single() {
+        let w_c = TAU * 1000. / BASE_SAMPLE_RATE;
+        let mut rng = Rng::new(3);
+        let input: Vec<f32> = (0..4096).map(|_| rng.next_f32()).collect();
+
+        for mode in (0..Mode::variants().len()).map(Mode::from_index) {
+            let single = render(mode.filter_mode(), w_c, 0.5, 2., &input);
+            let double = render_precise(mode.filter_mode(), w_c, 0.5, 2., &input);
+
+            for (n, (a, b)) in single.iter().zip(double).enumerate() {
+                assert!((a - b).abs() < 1e-4, "{mode:?}[{n}]: {a} != {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn double_precision_lowers_the_noise_floor() {
+        let w_c = TAU * 20. / BASE_SAMPLE_RATE;
+        let (single, double) = precision_noise_floor(FilterMode::LP, w_c, 0.5, 1.);
+
+        assert!(double < -120., "{double} dB");
+        assert!(
+            double < single,
+            "{double} dB, {single} dB in single precision"
+        );
+    }
+
+    #[test]
+    fn random_automation_stays_bounded() {
+        for seed in 1..=4 {
+            for block_len in [1, 16, 64, 512] {
+                let peak = automation_stress(seed, 8192 / block_len, block_len);
+                // the most resonant bandpass peaks at 25, the loudest bell at 24 dB
+                assert!(peak < 1000., "seed {seed}, blocks of {block_len}: {peak}");
+            }
+        }
+    }
+
+    #[test]
+    fn bursts_decay_to_exact_zero() {
+        let w_c = TAU * 1000. / BASE_SAMPLE_RATE;
+        let hold = (SILENCE_HOLD_MS / 1000. * BASE_SAMPLE_RATE) as usize;
+        // the resonance's envelope falls by a factor of e every `1 / (res * w_c)` samples
+        let max_decay = hold + (24. / (MIN_RES * w_c)) as usize;
+
+        for mode in (0..Mode::variants().len()).map(Mode::from_index) {
+            let decay = decay_samples(mode.filter_mode(), w_c, MIN_RES, 4410, 22050);
+            let n = decay.unwrap_or_else(|| panic!("{mode:?} never went silent"));
+            // the output has to stay under the silence level for the whole hold
+            assert!((hold..=max_decay).contains(&n), "{mode:?}: {n}");
+        }
+    }
+
+    #[test]
+    fn smoothing_lasts_as_long_at_any_rate_and_block_size() {
+        for sample_rate in [44100., 48000., 96000., 192000.] {
+            for block_len in [1, 32, 64, 441, 512, 4096] {
+                let ms = 1000. * smoothing_duration(sample_rate, block_len);
+                // within the sample the ramp's length is rounded to
+                assert!(
+                    (ms - SMOOTHING_MS).abs() <= 1000. / sample_rate,
+                    "{sample_rate} Hz, blocks of {block_len}: {ms} ms"
+                );
+            }
+        }
+    }
+}